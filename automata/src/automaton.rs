@@ -1,4 +1,5 @@
-use crate::{state::State, result::{Error, IResult}};
+use std::collections::BTreeSet;
+use crate::{input::Input, state::State, result::IResult};
 
 pub trait FiniteAutomaton<'a> {
     /// 创建一个新的，空的有限自动机
@@ -9,8 +10,21 @@ pub trait FiniteAutomaton<'a> {
     /// 添加终态
     fn add_finite_states<I>(&mut self, finite_states: I) -> IResult<()>
         where I: Iterator<Item = &'a str>;
+    /// 声明字母表中的输入字符，只加入 `feasible_inputs` 而不添加任何弧，ɛ 会被忽略
+    fn declare_alphabet<I>(&mut self, symbols: I)
+        where I: Iterator<Item = &'a str>;
     /// 添加一条转换规则
-    fn add_transfer_rule(&mut self, from_state_id: &str, input_str: &str, to_state_id: &str) -> IResult<()>;
+    fn add_transfer_rule(&mut self, from_state_id: &str, input_str: &str, to_state_id: &str) -> IResult<()> {
+        self.add_transfer_input(from_state_id, Input::new(input_str), to_state_id)
+    }
+    /// 添加一条转换规则，输入可以是字符区间
+    fn add_transfer_input(&mut self, from_state_id: &str, input: Input, to_state_id: &str) -> IResult<()>;
+    /// 判断当前有限状态机中是否有 id 为 state_id 的状态 (包括没有任何弧的初态与终态)
+    fn contains_state(&self, state_id: &str) -> bool;
+    /// 判断当前有限状态机中是否有转换规则 (from_state_id, input_str, to_state_id)
+    fn has_rule(&self, from_state_id: &str, input_str: &str, to_state_id: &str) -> bool;
+    /// 求出状态转换图 (忽略弧上的输入) 的所有强连通分量，按逆拓扑序返回，即每个分量能到达的分量都排在它前面
+    fn strongly_connected_components(&self) -> Vec<BTreeSet<State>>;
     /// 返回一个包含当前有限状态机中所有状态的 `Iterator`
     fn get_all_states_iter(&'a self)                    -> Box<dyn Iterator<Item = &'a State> + 'a>;
     /// 返回一个包含当前有限状态机中所有状态的 `IntoIterator`
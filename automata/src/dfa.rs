@@ -1,17 +1,23 @@
-use std::borrow::Borrow;
 use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
-use std::fmt::{Debug, Formatter, write};
-use std::hash::Hash;
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::iter::once;
-use std::ptr::NonNull;
-use bimap::BiBTreeMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use crate::automaton::FiniteAutomaton;
-use crate::edge::Edge;
-use crate::input::Input;
+use crate::disjoint_set::DisjointSet;
+use crate::display;
+use crate::dot;
+use crate::dsl;
+use crate::edge::{self, Edge};
+use crate::input::{self, Input};
 use crate::nfa::NFA;
 use crate::result::{Error, IResult};
 use crate::state::State;
+use crate::table;
 
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DFA {
     /// 唯一初态
     pub initial_state: Option<State>,
@@ -20,13 +26,78 @@ pub struct DFA {
     /// 合法输入字符集
     pub feasible_inputs: BTreeSet<Input>,
     /// 邻接矩阵，用于存储状态转换图中的所有弧
-    pub adjacency_matrix: BTreeMap<State, BTreeMap<State, Edge>>
+    /// 注意：直接修改此字段不会使转换索引失效，修改后需要重新调用 [`DFA::build_index`]
+    pub adjacency_matrix: BTreeMap<State, BTreeMap<State, Edge>>,
+    /// 转换索引，由 [`DFA::build_index`] 建立，供 [`DFA::accepts_fast`] 使用
+    /// DFA 被修改时清空。不参与序列化与相等性比较
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub transition_index: Option<HashMap<(State, Input), State>>,
+    /// 是否忽略大小写，见 [`DFA::set_case_insensitive`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub case_insensitive: bool,
+    /// 缺少转换时是否停留在当前状态，见 [`DFA::set_self_loop_on_missing`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub self_loop_on_missing: bool,
+    /// 附加在状态上的数据，例如终态所对应的记号类别，见 [`DFA::set_state_payload`]
+    /// 重新编号、重命名与删除状态时随状态迁移；除 [`DFA::minimize_with_payloads`] 外，[`DFA::minimize`] 等构造新 DFA 的方法不会保留
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub state_payloads: BTreeMap<State, String>,
+}
+
+/// 比较的是结构而非语言：只有初态、终态、字母表与所有弧都相同的两个 DFA 才相等，
+/// 接受同一语言但状态名不同的两个 DFA 并不相等。不考虑转换索引
+impl PartialEq for DFA {
+    fn eq(&self, other: &Self) -> bool {
+        self.initial_state == other.initial_state
+            && self.finite_states == other.finite_states
+            && self.feasible_inputs == other.feasible_inputs
+            && self.adjacency_matrix == other.adjacency_matrix
+            && self.case_insensitive == other.case_insensitive
+            && self.self_loop_on_missing == other.self_loop_on_missing
+            && self.state_payloads == other.state_payloads
+    }
+}
+
+impl Eq for DFA {}
+
+impl Hash for DFA {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.initial_state.hash(state);
+        self.finite_states.hash(state);
+        self.feasible_inputs.hash(state);
+        self.adjacency_matrix.hash(state);
+        self.case_insensitive.hash(state);
+        self.self_loop_on_missing.hash(state);
+        self.state_payloads.hash(state);
+    }
+}
+
+/// DFA 执行过程中的一步，见 [`DFA::trace`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceStep {
+    /// 当前状态
+    pub from_state: State,
+    /// 读入的输入字符
+    pub input: String,
+    /// 转换到的状态，找不到可用的转换规则时为 `None`
+    pub to_state: Option<State>,
+    /// to_state 是否为终态
+    pub accepting: bool,
 }
 
 impl<'a> FiniteAutomaton<'a> for DFA {
 
     fn new() -> Self {
-        todo!()
+        DFA {
+            initial_state: None,
+            finite_states: BTreeSet::new(),
+            feasible_inputs: BTreeSet::new(),
+            adjacency_matrix: BTreeMap::new(),
+            transition_index: None,
+            case_insensitive: false,
+            self_loop_on_missing: false,
+            state_payloads: BTreeMap::new(),
+        }
     }
 
     fn add_initial_states<I>(&mut self, initial_states: I) -> IResult<()>
@@ -37,9 +108,9 @@ impl<'a> FiniteAutomaton<'a> for DFA {
         let vec = initial_states.collect::<Vec<&str>>();
         if vec.len() == 1 {
             self.initial_state.replace( unsafe { State::new(*vec.get_unchecked(0)) });
-            return Ok(());
+            Ok(())
         } else {
-            return Err(Error::IllegalArgument("Unexpected number of initial states. A DFA has at most one state.")); // initial_states 为空
+            Err(Error::IllegalArgument("Unexpected number of initial states. A DFA has at most one state.")) // initial_states 为空
         }
     }
 
@@ -51,27 +122,59 @@ impl<'a> FiniteAutomaton<'a> for DFA {
         Ok(())
     }
 
-    fn add_transfer_rule(&mut self, from_state_id: &str, input_str: &str, to_state_id: &str) -> IResult<()> {
+    fn declare_alphabet<I>(&mut self, symbols: I)
+        where I: Iterator<Item = &'a str> {
+        let case_insensitive = self.case_insensitive;
+        self.feasible_inputs.extend(symbols.filter(|s| *s != "ɛ")
+            .map(|s| if case_insensitive { Input::new(s).to_lowercase() } else { Input::new(s) }));
+    }
+
+    fn add_transfer_input(&mut self, from_state_id: &str, input: Input, to_state_id: &str) -> IResult<()> {
         let from_state = State::new(from_state_id);
         let to_state = State::new(to_state_id);
-        let input = Input::new(input_str);
+        let input = if self.case_insensitive { input.to_lowercase() } else { input };
 
-        if input_str != "ɛ" {
-            self.feasible_inputs.insert(input.clone());
+        if input.is_epsilon() {
+            return Err(Error::IllegalArgument("A DFA has no ɛ transitions."));
+        }
+        // 同一状态经同一输入字符只能转换到唯一的状态，因此转换到其他状态的输入不能与 input 重叠
+        if let Some(to_map) = self.adjacency_matrix.get(&from_state) {
+            if to_map.iter().any(|(s, e)| *s != to_state && e.input_set.iter().any(|i| i.overlaps(&input))) {
+                return Err(Error::UnsupportedOperation("nondeterministic transition"));
+            }
         }
 
+        self.feasible_inputs.insert(input.clone());
+
         self.adjacency_matrix
             .entry(from_state)
-            .or_insert(BTreeMap::new())
+            .or_default()
             .entry(to_state.clone())
-            .and_modify(|e| e.add_input(input_str))
-            .or_insert(Edge::with_inputs([input]));
+            .and_modify(|e| e.add_input(input.clone()))
+            .or_insert_with(|| Edge::with_inputs([input]));
+
+        self.adjacency_matrix.entry(to_state).or_default();
 
-        self.adjacency_matrix.try_insert(to_state, BTreeMap::new());
+        self.transition_index = None;
 
         Ok(())
     }
 
+    fn contains_state(&self, state_id: &str) -> bool {
+        let state = State::new(state_id);
+        self.adjacency_matrix.contains_key(&state) || self.initial_state.as_ref() == Some(&state) || self.finite_states.contains(&state)
+    }
+
+    fn has_rule(&self, from_state_id: &str, input_str: &str, to_state_id: &str) -> bool {
+        self.adjacency_matrix.get(&State::new(from_state_id))
+            .and_then(|to_map| to_map.get(&State::new(to_state_id)))
+            .is_some_and(|edge| edge.input_set.contains(&Input::new(input_str)))
+    }
+
+    fn strongly_connected_components(&self) -> Vec<BTreeSet<State>> {
+        edge::strongly_connected_components(&self.adjacency_matrix)
+    }
+
     fn get_all_states_iter(&'a self) -> Box<dyn Iterator<Item = &'a State> + 'a> {
         Box::new(self.adjacency_matrix.keys())
     }
@@ -100,4 +203,2371 @@ impl<'a> FiniteAutomaton<'a> for DFA {
     fn get_states_num(&self) -> usize {
         self.adjacency_matrix.len()
     }
+}
+
+impl DFA {
+    /// 返回从 from_state 出发，经过一条覆盖 input 的弧到达的状态
+    /// 若不存在这样的弧（即进入隐式的死状态），返回 `None`
+    fn next_state(&self, from_state: &State, input: &Input) -> Option<&State> {
+        let to_map = self.adjacency_matrix.get(from_state)?;
+        let covers = |i: &Input| i.covers(input) || match input {
+            Input::Char(s) => self.case_insensitive && i.matches_str_ignore_case(s),
+            Input::Range(..) | Input::Any | Input::Not { .. } => false,
+        };
+        to_map.iter().find(|(_, e)| e.input_set.iter().any(covers))
+            .map(|(to_state, _)| to_state)
+    }
+
+    /// 将 `feasible_inputs` 切分为互不相交的输入类，见 [`input::alphabet_classes`]
+    /// 每个状态经同一输入类至多转换到一个状态，逐个输入地遍历转换函数的算法应在这些输入类上进行
+    fn input_classes(&self) -> BTreeSet<Input> {
+        input::alphabet_classes(&self.feasible_inputs)
+    }
+
+    /// 设置是否忽略大小写。开启后，之后加入的输入字符会被转为小写，
+    /// 并且 [`DFA::accepts`]、[`DFA::trace`] 等模拟方法忽略大小写地匹配输入字符；已有的弧不会被改写
+    pub fn set_case_insensitive(&mut self, v: bool) {
+        self.case_insensitive = v;
+    }
+
+    /// 设置缺少转换时是否停留在当前状态。开启后，[`DFA::run`]、[`DFA::accepts`] 与 [`DFA::accepts_fast`]
+    /// 遇到缺少的 (状态, 输入) 转换时视为一个自环，而不是进入隐式的死状态
+    /// 注意这会改变 DFA 所识别的语言，而最小化、求补等其他方法仍按缺少转换即拒绝的语义处理
+    pub fn set_self_loop_on_missing(&mut self, v: bool) {
+        self.self_loop_on_missing = v;
+    }
+
+    /// 返回从 state 出发的所有转换 (输入, 目标状态)，按目标状态排序，弧上的每个输入单独列出；state 不存在时返回空的 `Vec`
+    pub fn transitions_from(&self, state: &str) -> Vec<(Input, State)> {
+        edge::transitions_from(&self.adjacency_matrix, state)
+    }
+
+    /// 为状态 state 附加数据 payload，例如词法分析中终态所对应的记号类别，已有的数据会被覆盖
+    pub fn set_state_payload(&mut self, state: &str, payload: String) {
+        self.state_payloads.insert(State::new(state), payload);
+    }
+
+    /// 返回附加在状态 state 上的数据，没有时返回 `None`
+    pub fn get_state_payload(&self, state: &str) -> Option<&String> {
+        self.state_payloads.get(&State::new(state))
+    }
+
+    /// 返回一个包含当前 DFA 中所有转换规则 (from_state, input, to_state) 的 `Iterator`
+    fn transfer_rules(&self) -> impl Iterator<Item = (&State, &Input, &State)> {
+        self.adjacency_matrix.iter().flat_map(|(from_state, to_map)| {
+            to_map.iter().flat_map(move |(to_state, edge)| {
+                edge.input_set.iter().map(move |input| (from_state, input, to_state))
+            })
+        })
+    }
+
+    /// 返回一个以 base 为前缀、当前 DFA 中尚未使用的状态 id
+    fn fresh_state_id(&self, base: &str) -> String {
+        let mut state_id = base.to_string();
+        while self.adjacency_matrix.contains_key(&State::new(state_id.as_str()))
+            || self.initial_state.as_ref().is_some_and(|s| s.state_id == state_id) {
+            state_id.push('\'');
+        }
+        state_id
+    }
+
+    /// 从初态出发读入整个输入串 `input`，返回最终到达的状态
+    /// 没有初态，或某一步找不到可用的转换规则（即进入隐式的死状态）时返回 `None`；
+    /// 开启 [`DFA::set_self_loop_on_missing`] 时，找不到可用的转换规则则停留在当前状态
+    pub fn run(&self, input: &[&str]) -> Option<State> {
+        let mut current_state = self.initial_state.as_ref()?;
+        for input_str in input {
+            current_state = match self.next_state(current_state, &Input::new(*input_str)) {
+                Some(s) => s,
+                None if self.self_loop_on_missing => current_state,
+                None => return None,
+            };
+        }
+        Some(current_state.clone())
+    }
+
+    /// 从初态出发逐个读入 `input` 中的输入字符，返回最长的、停在终态的前缀的长度，即词法分析中的最长匹配
+    /// 没有任何前缀被接受时返回 `None`；初态为终态时空前缀也算在内
+    pub fn longest_accepted_prefix(&self, input: &[&str]) -> Option<usize> {
+        let mut current_state = self.initial_state.as_ref()?;
+        let mut longest = self.finite_states.contains(current_state).then_some(0);
+        for (i, input_str) in input.iter().enumerate() {
+            current_state = match self.next_state(current_state, &Input::new(*input_str)) {
+                Some(s) => s,
+                None => break,
+            };
+            if self.finite_states.contains(current_state) {
+                longest = Some(i + 1);
+            }
+        }
+        longest
+    }
+
+    /// 在 `input` 中非锚定地查找第一个被接受的子串，返回其起止下标 `(start, end)`，即 `input[start..end]` 被接受
+    /// 依次尝试每个起点，取第一个有匹配的起点上的最长匹配；与要求整个输入串被接受的 [`DFA::accepts`] 不同
+    pub fn find(&self, input: &[&str]) -> Option<(usize, usize)> {
+        (0..=input.len()).find_map(|start| {
+            self.longest_accepted_prefix(&input[start..]).map(|len| (start, start + len))
+        })
+    }
+
+    /// 判断输入串 `input` 能否被当前 DFA 接受，`input` 中每个元素为一个输入字符
+    /// 若某一步找不到可用的转换规则（即进入隐式的死状态），则拒绝
+    pub fn accepts(&self, input: &[&str]) -> bool {
+        self.run(input).is_some_and(|s| self.finite_states.contains(&s))
+    }
+
+    /// 与 [`DFA::accepts`] 相同，但 `input` 中有不属于 `feasible_inputs` 的输入字符时抛出 [`Error::IllegalArgument`]，
+    /// 以区分“因结构而被拒绝”与“输入中含有未知的输入字符”
+    pub fn accepts_checked(&self, input: &[&str]) -> IResult<bool> {
+        if !input.iter().all(|s| self.feasible_inputs.iter()
+            .any(|i| i.matches_str(s) || (self.case_insensitive && i.matches_str_ignore_case(s)))) {
+            return Err(Error::IllegalArgument("Input contains a symbol outside feasible_inputs."));
+        }
+        Ok(self.accepts(input))
+    }
+
+    /// 建立转换索引，之后 [`DFA::accepts_fast`] 每一步只需一次哈希查找
+    /// 通过 `add_*`、`remove_*` 等方法修改 DFA 时索引会被清空，修改后需要重新调用此方法
+    pub fn build_index(&mut self) {
+        let index = self.transfer_rules()
+            .map(|(from_state, input, to_state)| ((from_state.clone(), input.clone()), to_state.clone()))
+            .collect();
+        self.transition_index = Some(index);
+    }
+
+    /// 与 [`DFA::accepts`] 相同，但使用 [`DFA::build_index`] 建立的转换索引加速查找
+    /// 尚未建立索引时退化为 [`DFA::accepts`]；索引中找不到 (例如输入由字符区间匹配) 时退回逐条弧查找
+    pub fn accepts_fast(&self, input: &[&str]) -> bool {
+        let index = match &self.transition_index {
+            Some(index) => index,
+            None => return self.accepts(input),
+        };
+        let mut current_state = match &self.initial_state {
+            Some(s) => s,
+            None => return false,
+        };
+        for input_str in input {
+            let input = Input::new(*input_str);
+            let key = (current_state.clone(), input);
+            match index.get(&key).or_else(|| self.next_state(current_state, &key.1)) {
+                Some(s) => current_state = s,
+                None if self.self_loop_on_missing => {},
+                None => return false,
+            }
+        }
+        self.finite_states.contains(current_state)
+    }
+
+    /// 依次判断 `inputs` 中的每个输入串能否被当前 DFA 接受，返回与之一一对应的结果
+    /// 已经用 [`DFA::build_index`] 建立了转换索引时，所有输入串共用该索引
+    pub fn accepts_all<'s, I>(&self, inputs: I) -> Vec<bool>
+        where I: IntoIterator<Item = &'s [&'s str]> {
+        inputs.into_iter().map(|input| self.accepts_fast(input)).collect()
+    }
+
+    /// 判断记号序列 `tokens` 能否被当前 DFA 接受，每个记号作为一个输入字符参与一步转换，
+    /// 因此 DFA 可以作为简单文法的记号级识别器。与 [`DFA::accepts`] 的行为相同
+    pub fn accepts_tokens(&self, tokens: &[&str]) -> bool {
+        self.accepts(tokens)
+    }
+
+    /// 逐步执行输入串 `input`，记录每一步的当前状态、读入的输入字符与转换到的状态
+    /// 在第一个找不到转换规则的位置停止，此时最后一步的 `to_state` 为 `None`；没有初态时返回空的记录
+    pub fn trace(&self, input: &[&str]) -> Vec<TraceStep> {
+        let mut steps = Vec::new();
+        let mut current_state = match &self.initial_state {
+            Some(s) => s,
+            None => return steps,
+        };
+        for input_str in input {
+            let to_state = self.next_state(current_state, &Input::new(*input_str));
+            steps.push(TraceStep {
+                from_state: current_state.clone(),
+                input: input_str.to_string(),
+                to_state: to_state.cloned(),
+                accepting: to_state.is_some_and(|s| self.finite_states.contains(s)),
+            });
+            match to_state {
+                Some(s) => current_state = s,
+                None => break,
+            }
+        }
+        steps
+    }
+
+    /// 删除一条转换规则，弧上不再有任何输入时删除整条弧，已没有任何弧使用的输入会从 `feasible_inputs` 中删除
+    /// 起点或终点不存在时抛出 [`Error::StateNotFound`]，规则不存在时抛出 [`Error::IllegalArgument`]
+    pub fn remove_transfer_rule(&mut self, from_state_id: &str, input_str: &str, to_state_id: &str) -> IResult<()> {
+        edge::remove_transfer_rule(&mut self.adjacency_matrix, &mut self.feasible_inputs,
+                                   from_state_id, &Input::new(input_str), to_state_id)?;
+        self.transition_index = None;
+        Ok(())
+    }
+
+    /// 将所有弧上的输入字符 old 重命名为 new，并更新 `feasible_inputs`
+    /// 若同一条弧上已有 new，则 old 与之合并；若某个状态经 old 与经 new 转换到不同的状态，则重命名会造成不确定，
+    /// 此时不做任何修改并抛出 [`Error::IllegalArgument`]。old 或 new 为 ɛ，或 old 不是合法输入字符时同样抛出该错误
+    pub fn rename_input(&mut self, old: &str, new: &str) -> IResult<()> {
+        let (old, new) = (Input::new(old), Input::new(new));
+        for to_map in self.adjacency_matrix.values() {
+            let target_of = |input: &Input| to_map.iter().find(|(_, e)| e.input_set.contains(input)).map(|(s, _)| s);
+            if let (Some(t1), Some(t2)) = (target_of(&old), target_of(&new)) {
+                if t1 != t2 {
+                    return Err(Error::IllegalArgument("Renaming the input would make the DFA nondeterministic."));
+                }
+            }
+        }
+        edge::rename_input(&mut self.adjacency_matrix, &mut self.feasible_inputs, &old, &new)?;
+        self.transition_index = None;
+        Ok(())
+    }
+
+    /// 消耗当前 DFA，按起点、终点、输入的顺序逐个产生其中的转换规则 (from_state, input, to_state)
+    /// 状态与输入直接从邻接矩阵中移出；没有任何弧的状态不会出现在结果中
+    pub fn into_transitions(self) -> impl Iterator<Item = (State, Input, State)> {
+        edge::into_transitions(self.adjacency_matrix)
+    }
+
+    /// 返回当前 DFA 中转换规则的数量，即不同的 (from_state, input, to_state) 三元组的数量
+    pub fn num_edges(&self) -> usize {
+        self.adjacency_matrix.values()
+            .flat_map(|to_map| to_map.values())
+            .map(|edge| edge.input_set.len())
+            .sum()
+    }
+
+    /// 返回当前 DFA 中 ɛ 转换规则的数量
+    pub fn num_epsilon_edges(&self) -> usize {
+        self.adjacency_matrix.values()
+            .flat_map(|to_map| to_map.values())
+            .filter(|edge| edge.input_set.iter().any(Input::is_epsilon))
+            .count()
+    }
+
+    /// 返回当前 DFA 的字母表大小，即 `feasible_inputs` 中输入的数量
+    pub fn alphabet_size(&self) -> usize {
+        self.feasible_inputs.len()
+    }
+
+    /// 将所有状态重新命名为 "0", "1", ...，编号按从初态出发、依输入类顺序进行的广度优先搜索顺序确定
+    /// 不可达的状态按原有顺序排在最后
+    pub fn normalize_ids(&mut self) {
+        let mut order = Vec::new();
+        let mut visited = BTreeSet::from_iter(self.initial_state.iter());
+        let mut search_queue = VecDeque::from_iter(self.initial_state.iter()); // 搜索队列
+        let inputs = self.input_classes();
+        while let Some(front_state) = search_queue.pop_front() {
+            order.push(front_state);
+            for input in &inputs {
+                if let Some(to_state) = self.next_state(front_state, input) {
+                    if visited.insert(to_state) {
+                        search_queue.push_back(to_state);
+                    }
+                }
+            }
+        }
+        order.extend(self.get_all_states_iter().chain(self.finite_states.iter()).filter(|s| visited.insert(s)));
+        let new_ids = order.into_iter().enumerate()
+            .map(|(i, s)| (s.clone(), State::new(i.to_string())))
+            .collect::<BTreeMap<_, _>>();
+
+        self.initial_state = self.initial_state.take().map(|s| new_ids[&s].clone());
+        self.finite_states = self.finite_states.iter().map(|s| new_ids[s].clone()).collect();
+        self.state_payloads = std::mem::take(&mut self.state_payloads).into_iter()
+            .filter_map(|(s, payload)| new_ids.get(&s).map(|new_id| (new_id.clone(), payload)))
+            .collect();
+        self.adjacency_matrix = std::mem::take(&mut self.adjacency_matrix).into_iter()
+            .map(|(from_state, to_map)| (new_ids[&from_state].clone(), to_map.into_iter()
+                .map(|(to_state, edge)| (new_ids[&to_state].clone(), edge))
+                .collect()))
+            .collect();
+        self.transition_index = None;
+    }
+
+    /// 将状态 old 重命名为 new，包括邻接矩阵中的行与弧的终点、初态与终态集
+    /// old 不存在时抛出 [`Error::StateNotFound`]；new 已存在时抛出 [`Error::IllegalArgument`]，以免意外合并两个状态
+    pub fn rename_state(&mut self, old: &str, new: &str) -> IResult<()> {
+        if !self.contains_state(old) {
+            return Err(Error::StateNotFound(old.to_string()));
+        }
+        if self.contains_state(new) {
+            return Err(Error::IllegalArgument("The new state id already exists."));
+        }
+        let (old, new) = (State::new(old), State::new(new));
+        edge::rename_state(&mut self.adjacency_matrix, &old, &new);
+        if self.initial_state.as_ref() == Some(&old) {
+            self.initial_state = Some(new.clone());
+        }
+        if let Some(payload) = self.state_payloads.remove(&old) {
+            self.state_payloads.insert(new.clone(), payload);
+        }
+        if self.finite_states.remove(&old) {
+            self.finite_states.insert(new);
+        }
+        self.transition_index = None;
+        Ok(())
+    }
+
+    /// 将当前 DFA 渲染为状态转换表，列为排好序的 `feasible_inputs`
+    /// 初态以 `->` 标记，终态以 `*` 标记，缺少转换的单元格为空
+    pub fn to_table(&self) -> String {
+        let inputs = self.feasible_inputs.iter().cloned().collect::<Vec<_>>();
+        let initial_states = self.initial_state.iter().cloned().collect();
+        table::to_table(&initial_states, &self.finite_states, &inputs, &self.adjacency_matrix)
+    }
+
+    /// 将当前 DFA 的转换函数导出为稠密的二维表，返回 (排好序的状态 id, 排好序的输入类, 表)
+    /// 列为 `feasible_inputs` 切分得到的互不相交的输入类，只有字符时即为 `feasible_inputs` 本身
+    /// 表的第 i 行第 j 列为第 i 个状态经第 j 个输入转换到的状态的下标，缺少转换时为 `None`
+    pub fn to_dense_table(&self) -> (Vec<String>, Vec<String>, Vec<Vec<Option<usize>>>) {
+        let states = self.all_states();
+        let inputs = self.input_classes();
+        let index = states.iter().enumerate().map(|(i, s)| (*s, i)).collect::<BTreeMap<_, _>>();
+        let table = states.iter()
+            .map(|s| inputs.iter()
+                .map(|input| self.next_state(s, input).map(|to_state| index[to_state]))
+                .collect())
+            .collect();
+        (states.into_iter().map(|s| s.state_id.clone()).collect(),
+         inputs.iter().map(Input::to_string).collect(),
+         table)
+    }
+
+    /// 将当前 DFA 写为 [`NFA::from_dsl`] 所述格式的文本描述，输出稳定，见 [`NFA::to_dsl`]
+    pub fn to_dsl(&self) -> String {
+        let initial_states = self.initial_state.iter().cloned().collect();
+        dsl::to_dsl(&initial_states, &self.finite_states, &self.adjacency_matrix)
+    }
+
+    /// 将当前 DFA 渲染为 Graphviz DOT 格式
+    pub fn to_dot(&self) -> String {
+        dot::to_dot(self.initial_state.iter(), &self.finite_states, &self.adjacency_matrix)
+    }
+
+    /// 计算当前 DFA 所接受语言的规范形式的哈希值：删除不可达状态与死状态、最小化并规范化状态 id 后，
+    /// 对初态、终态集与转换规则求哈希，因此接受相同语言的 DFA 得到相同的哈希值，可用于按语言去重
+    /// 只是一种便利手段：哈希值相同的 DFA 仍可能接受不同的语言，需要时用 [`DFA::is_subset_of`] 双向确认
+    pub fn canonical_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        // 接受空语言的 DFA 都等价，直接返回固定的哈希值
+        if self.is_empty() {
+            return hasher.finish();
+        }
+        let mut dfa = self.clone();
+        dfa.remove_unreachable();
+        dfa.remove_dead_states();
+        let mut dfa = dfa.minimize();
+        dfa.normalize_ids();
+        dfa.initial_state.hash(&mut hasher);
+        dfa.finite_states.hash(&mut hasher);
+        dfa.into_transitions().for_each(|rule| rule.hash(&mut hasher));
+        hasher.finish()
+    }
+
+    /// 最小化当前 DFA，使用分割法
+    /// 初始时将所有状态分为终态、非终态两类，之后不断细分，直到每一类中的状态
+    /// 对任一输入都转换到同一类中为止。最终每一类合并为新 DFA 中的一个状态，以该类代表元的 id 命名
+    pub fn minimize(&self) -> DFA {
+        self.quotient(self.refine_partition(self.finality_partition()), false)
+    }
+
+    /// 返回 [`DFA::minimize`] 最终得到的划分，即会被合并为同一状态的各类原状态，但不构造最小化的 DFA
+    /// 各类按其中最小的状态排序
+    pub fn minimization_partition(&self) -> Vec<BTreeSet<State>> {
+        let mut classes = BTreeMap::new();
+        for (s, class_id) in self.refine_partition(self.finality_partition()) {
+            classes.entry(class_id).or_insert_with(BTreeSet::new).insert(s.clone());
+        }
+        let mut classes = classes.into_values().collect::<Vec<_>>();
+        classes.sort();
+        classes
+    }
+
+    /// 返回所有状态，包括没有任何弧的初态与终态
+    fn all_states(&self) -> BTreeSet<&State> {
+        self.get_all_states_iter()
+            .chain(self.initial_state.iter())
+            .chain(self.finite_states.iter())
+            .collect()
+    }
+
+    /// 分割法的初始划分，终态为一类，非终态为另一类
+    fn finality_partition(&self) -> BTreeMap<&State, usize> {
+        self.all_states().into_iter()
+            .map(|s| (s, self.finite_states.contains(s) as usize))
+            .collect()
+    }
+
+    /// 与 [`DFA::minimize`] 相同，但附加数据不同的两个终态视为可区分的，因此不会被合并，适用于词法分析器的生成
+    /// 得到的 DFA 中，每个状态保留其所代表的原状态上附加的数据
+    pub fn minimize_with_payloads(&self) -> DFA {
+        let mut keys = BTreeMap::new();
+        let class_of = self.all_states().into_iter()
+            .map(|s| {
+                let is_finite = self.finite_states.contains(s);
+                let key = (is_finite, if is_finite { self.state_payloads.get(s) } else { None });
+                let num_keys = keys.len();
+                (s, *keys.entry(key).or_insert(num_keys))
+            })
+            .collect();
+        self.quotient(self.refine_partition(class_of), true)
+    }
+
+    /// 分割法的细分过程，class_of 为每个状态初始所属的类，返回稳定后每个状态所属的类
+    fn refine_partition<'s>(&'s self, mut class_of: BTreeMap<&'s State, usize>) -> BTreeMap<&'s State, usize> {
+        let mut num_classes = class_of.values().collect::<BTreeSet<_>>().len();
+        let inputs = self.input_classes();
+        loop {
+            // 以 (当前所属类, 每个输入转换到的类) 作为签名，签名相同的状态分到同一类
+            let mut signatures = BTreeMap::new();
+            let mut new_class_of = BTreeMap::new();
+            for s in self.all_states() {
+                let signature = (class_of[s], inputs.iter()
+                    .map(|input| self.next_state(s, input).map(|t| class_of[t]))
+                    .collect::<Vec<_>>());
+                let num_signatures = signatures.len();
+                let class_id = *signatures.entry(signature).or_insert(num_signatures);
+                new_class_of.insert(s, class_id);
+            }
+            class_of = new_class_of;
+            // 没有任何一类被细分，划分已稳定
+            if signatures.len() == num_classes {
+                return class_of;
+            }
+            num_classes = signatures.len();
+        }
+    }
+
+    /// 将 class_of 中同一类的状态合并为一个状态，得到商自动机；keep_payloads 为真时保留附加数据
+    fn quotient(&self, class_of: BTreeMap<&State, usize>, keep_payloads: bool) -> DFA {
+        // 用并查集记录最终的划分，每一类的根即为该类的代表元
+        let mut classes = BTreeMap::new();
+        for (s, class_id) in class_of {
+            classes.entry(class_id).or_insert_with(Vec::new).push(s.to_owned());
+        }
+        let mut disjoint_set = DisjointSet::new();
+        classes.into_values().for_each(|class| disjoint_set.add_elements(class));
+        let representative = |s: &State| disjoint_set.get_father(s).unwrap().state_id.as_str();
+
+        let mut dfa = DFA::new();
+        if let Some(initial_state) = &self.initial_state {
+            dfa.add_initial_states(once(representative(initial_state))).unwrap();
+        }
+        dfa.add_finite_states(self.finite_states.iter().map(representative)).unwrap();
+        for (from_state, to_map) in &self.adjacency_matrix {
+            for (to_state, edge) in to_map {
+                for input in &edge.input_set {
+                    dfa.add_transfer_input(representative(from_state), input.clone(), representative(to_state)).unwrap();
+                }
+            }
+        }
+        if keep_payloads {
+            for (s, payload) in &self.state_payloads {
+                if self.contains_state(&s.state_id) {
+                    dfa.state_payloads.entry(State::new(representative(s))).or_insert_with(|| payload.clone());
+                }
+            }
+        }
+        // 规则已按原样加入，最后再设置标志，避免输入被转为小写后产生冲突
+        dfa.set_case_insensitive(self.case_insensitive);
+        dfa
+    }
+
+    /// 用填表法求出所有可区分的状态对 (p, q)，其中 p < q，不在结果中的状态对即为等价状态
+    /// 先标记一个是终态、另一个不是的状态对，再反复标记在某个输入上转换到已标记状态对的状态对，直到没有新的标记
+    /// 缺少的转换视为进入隐式的死状态
+    pub fn distinguishable_pairs(&self) -> BTreeSet<(State, State)> {
+        let states = self.get_all_states_iter().collect::<Vec<_>>();
+        let live_states = self.live_states();
+        let inputs = self.input_classes();
+        let mut marked = BTreeSet::new();
+        for (i, p) in states.iter().enumerate() {
+            for q in &states[i + 1..] {
+                if self.finite_states.contains(*p) != self.finite_states.contains(*q) {
+                    marked.insert((*p, *q));
+                }
+            }
+        }
+        loop {
+            let mut changed = false;
+            for (i, p) in states.iter().enumerate() {
+                for q in &states[i + 1..] {
+                    if marked.contains(&(*p, *q)) {
+                        continue;
+                    }
+                    let distinguishable = inputs.iter().any(|input| {
+                        match (self.next_state(p, input), self.next_state(q, input)) {
+                            (Some(t1), Some(t2)) => marked.contains(&(t1.min(t2), t1.max(t2))),
+                            // 隐式的死状态与能到达终态的状态可区分
+                            (Some(t), None) | (None, Some(t)) => live_states.contains(t),
+                            (None, None) => false,
+                        }
+                    });
+                    if distinguishable {
+                        marked.insert((*p, *q));
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        marked.into_iter().map(|(p, q)| (p.clone(), q.clone())).collect()
+    }
+
+    /// 检查当前 DFA 的结构不变式：有初态，`feasible_inputs` 中没有 ɛ，每个状态上转换到不同状态的输入互不重叠，
+    /// 并且每条弧的终点都是邻接矩阵的 key。用于检查直接修改字段构造的 DFA，在第一处违反时抛出相应的错误
+    pub fn validate(&self) -> IResult<()> {
+        if self.initial_state.is_none() {
+            return Err(Error::Uninitialized("DFA has no initial state"));
+        }
+        if self.feasible_inputs.iter().any(Input::is_epsilon) {
+            return Err(Error::IllegalArgument("ɛ must not be in feasible_inputs"));
+        }
+        if self.adjacency_matrix.values().any(edge::has_conflicting_rules) {
+            return Err(Error::UnsupportedOperation("nondeterministic transition"));
+        }
+        edge::check_targets(&self.adjacency_matrix)
+    }
+
+    /// 判断当前 DFA 是否完全，即每个状态 (包括初态) 对 `feasible_inputs` 中的每个输入字符都有转换
+    pub fn is_complete(&self) -> bool {
+        let inputs = self.input_classes();
+        self.get_all_states_iter()
+            .chain(self.initial_state.iter())
+            .all(|s| inputs.iter().all(|input| self.next_state(s, input).is_some()))
+    }
+
+    /// 补全当前 DFA：添加一个带有自环的陷阱状态，并将所有缺失的转换指向它
+    /// 若当前 DFA 已经完全，则不做任何修改
+    pub fn complete(&mut self) {
+        let mut missing = Vec::new();
+        let inputs = self.input_classes();
+        for s in self.get_all_states_iter().chain(self.initial_state.iter()).collect::<BTreeSet<_>>() {
+            for input in &inputs {
+                if self.next_state(s, input).is_none() {
+                    missing.push((s.state_id.clone(), input.clone()));
+                }
+            }
+        }
+        if missing.is_empty() {
+            return;
+        }
+        // 缺失的转换不会与已有的转换冲突，因此下面的 unwrap 不会失败
+        let trap_state_id = self.fresh_state_id("trap");
+        for (state_id, input) in missing {
+            self.add_transfer_input(&state_id, input, &trap_state_id).unwrap();
+        }
+        for input in inputs {
+            self.add_transfer_input(&trap_state_id, input, &trap_state_id).unwrap();
+        }
+    }
+
+    /// 返回所有陷阱状态，即对 `feasible_inputs` 中的每个输入字符都转换回自身的非终态，一旦进入便不可能再被接受
+    /// 与死状态不同，缺少某个转换或能转换到其他状态的非终态不是陷阱状态
+    pub fn trap_states(&self) -> BTreeSet<State> {
+        let inputs = self.input_classes();
+        self.get_all_states_iter()
+            .filter(|s| !self.finite_states.contains(*s))
+            .filter(|s| inputs.iter().all(|input| self.next_state(s, input) == Some(*s)))
+            .cloned()
+            .collect()
+    }
+
+    /// 求当前 DFA 的补，得到的 DFA 在 `feasible_inputs` 上接受且仅接受当前 DFA 拒绝的串
+    /// 先用 [`DFA::complete`] 补全，再互换终态与非终态
+    pub fn complement(&self) -> DFA {
+        let mut dfa = DFA::new();
+        for (from_state, input, to_state) in self.transfer_rules() {
+            dfa.add_transfer_input(&from_state.state_id, input.clone(), &to_state.state_id).unwrap();
+        }
+        dfa.feasible_inputs.extend(self.feasible_inputs.iter().cloned());
+        // 没有初态的 DFA 不接受任何串，其补以一个新状态为初态，补全后从它出发的串都被接受
+        let initial_state_id = match &self.initial_state {
+            Some(s) => s.state_id.clone(),
+            None => self.fresh_state_id("start"),
+        };
+        dfa.add_initial_states(once(initial_state_id.as_str())).unwrap();
+        dfa.complete();
+
+        let finite_states = dfa.get_all_states_iter()
+            .chain(dfa.initial_state.iter())
+            .filter(|s| !self.finite_states.contains(s))
+            .map(|s| s.state_id.clone())
+            .collect::<Vec<_>>();
+        dfa.add_finite_states(finite_states.iter().map(String::as_str)).unwrap();
+        dfa.set_case_insensitive(self.case_insensitive);
+        dfa
+    }
+
+    /// 返回从初态出发可达的所有状态
+    fn reachable_states(&self) -> BTreeSet<State> {
+        let mut reachable_states = BTreeSet::new();
+        let mut search_queue = VecDeque::new(); // 搜索队列
+        if let Some(initial_state) = &self.initial_state {
+            reachable_states.insert(initial_state.clone());
+            search_queue.push_back(initial_state);
+        }
+        while let Some(front_state) = search_queue.pop_front() {
+            for to_state in self.adjacency_matrix.get(front_state).into_iter().flat_map(|map| map.keys()) {
+                if reachable_states.insert(to_state.clone()) {
+                    search_queue.push_back(to_state);
+                }
+            }
+        }
+        reachable_states
+    }
+
+    /// 删除所有从初态出发不可达的状态，以及与之相关的弧与终态
+    /// 若没有初态，则删除所有状态；`feasible_inputs` 保持不变
+    pub fn remove_unreachable(&mut self) {
+        let reachable_states = self.reachable_states();
+        self.adjacency_matrix.retain(|s, _| reachable_states.contains(s));
+        self.adjacency_matrix.values_mut()
+            .for_each(|to_map| to_map.retain(|s, _| reachable_states.contains(s)));
+        self.finite_states.retain(|s| reachable_states.contains(s));
+        self.state_payloads.retain(|s, _| reachable_states.contains(s));
+        self.transition_index = None;
+    }
+
+    /// 返回所有能够到达某个终态的状态 (即活状态)，及其到达终态所需的最少步数，通过在反向图上从终态出发搜索得到
+    fn distances_to_finite(&self) -> BTreeMap<&State, usize> {
+        let mut reversed_matrix: BTreeMap<&State, Vec<&State>> = BTreeMap::new();
+        for (from_state, to_map) in &self.adjacency_matrix {
+            for to_state in to_map.keys() {
+                reversed_matrix.entry(to_state).or_default().push(from_state);
+            }
+        }
+        let mut distances = self.finite_states.iter().map(|s| (s, 0)).collect::<BTreeMap<_, _>>();
+        let mut search_queue = VecDeque::from_iter(self.finite_states.iter()); // 搜索队列
+        while let Some(front_state) = search_queue.pop_front() {
+            let distance = distances[front_state] + 1;
+            for from_state in reversed_matrix.get(front_state).into_iter().flatten() {
+                if !distances.contains_key(from_state) {
+                    distances.insert(from_state, distance);
+                    search_queue.push_back(from_state);
+                }
+            }
+        }
+        distances
+    }
+
+    /// 返回所有能够到达某个终态的状态 (即活状态)
+    fn live_states(&self) -> BTreeSet<State> {
+        self.distances_to_finite().into_keys().cloned().collect()
+    }
+
+    /// 求当前 DFA 的前缀闭包，得到的 DFA 接受当前 DFA 所接受的串的所有前缀
+    /// 做法是将所有活状态 (能到达终态的状态) 都改为终态
+    pub fn prefix_closure(&self) -> DFA {
+        let mut dfa = self.clone();
+        dfa.finite_states = self.live_states();
+        dfa
+    }
+
+    /// 求当前 DFA 所接受的语言关于 prefix 的导数，即 { w | prefix w 被当前 DFA 接受 }
+    /// 以读入 prefix 后到达的状态为初态，并删除从它出发不可达的状态；prefix 中途卡住时得到不接受任何串的 DFA
+    pub fn derivative(&self, prefix: &[&str]) -> DFA {
+        let mut dfa = self.clone();
+        dfa.initial_state = self.run(prefix);
+        dfa.remove_unreachable();
+        dfa
+    }
+
+    /// 删除所有死状态 (无法到达任何终态的状态)，以及所有指向死状态的弧
+    /// 初态保持不变，即使它是死状态 (此时 DFA 不接受任何串)
+    pub fn remove_dead_states(&mut self) {
+        let live_states = self.live_states();
+        self.adjacency_matrix.retain(|s, _| live_states.contains(s));
+        self.adjacency_matrix.values_mut()
+            .for_each(|to_map| to_map.retain(|s, _| live_states.contains(s)));
+        self.state_payloads.retain(|s, _| live_states.contains(s));
+        self.transition_index = None;
+    }
+
+    /// 返回当前 DFA 接受的最短的串，长度相同时返回字典序最小的；若 DFA 不接受任何串，返回 `None`
+    pub fn shortest_accepted(&self) -> Option<Vec<String>> {
+        let initial_state = self.initial_state.as_ref()?;
+        // 记录每个已访问状态的前驱状态及所经过的输入字符
+        let mut predecessors: BTreeMap<&State, Option<(&State, &Input)>> = BTreeMap::from([(initial_state, None)]);
+        let mut search_queue = VecDeque::from([initial_state]); // 搜索队列
+        let inputs = self.input_classes();
+        while let Some(front_state) = search_queue.pop_front() {
+            if self.finite_states.contains(front_state) {
+                // 沿前驱回溯得到路径上的输入字符
+                let mut word = Vec::new();
+                let mut current_state = front_state;
+                while let Some((from_state, input)) = predecessors[current_state] {
+                    word.push(input.representative());
+                    current_state = from_state;
+                }
+                word.reverse();
+                return Some(word);
+            }
+            for input in &inputs {
+                if let Some(to_state) = self.next_state(front_state, input) {
+                    if !predecessors.contains_key(to_state) {
+                        predecessors.insert(to_state, Some((front_state, input)));
+                        search_queue.push_back(to_state);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// 从初态出发随机游走至多 max_len 步，每一步在当前状态的所有转换中等概率地选择一个，没有转换时提前停止
+    /// 停止时位于终态则返回读入的输入字符序列，否则返回 `None`。相同的 rng_seed 总是得到相同的结果
+    pub fn sample_accepted(&self, max_len: usize, rng_seed: u64) -> Option<Vec<String>> {
+        let mut current_state = self.initial_state.as_ref()?;
+        let mut rng_state = rng_seed;
+        let mut word = Vec::new();
+        for _ in 0..max_len {
+            let transitions = self.adjacency_matrix.get(current_state)
+                .into_iter()
+                .flat_map(|to_map| to_map.iter())
+                .flat_map(|(to_state, edge)| edge.input_set.iter().map(move |input| (input, to_state)))
+                .collect::<Vec<_>>();
+            if transitions.is_empty() {
+                break;
+            }
+            let (input, to_state) = transitions[(splitmix64(&mut rng_state) % transitions.len() as u64) as usize];
+            word.push(input.representative());
+            current_state = to_state;
+        }
+        self.finite_states.contains(current_state).then_some(word)
+    }
+
+    /// 按长度优先、字典序其次的顺序，返回当前 DFA 接受的所有长度不超过 max_len 的串
+    /// 只扩展仍能在剩余长度内到达终态的状态，但语言本身可能随长度指数增长，需要由 max_len 加以限制
+    pub fn enumerate_language(&self, max_len: usize) -> Vec<Vec<String>> {
+        let initial_state = match &self.initial_state {
+            Some(s) => s,
+            None => return Vec::new(),
+        };
+        let distances = self.distances_to_finite();
+        let inputs = self.input_classes();
+        let mut words = Vec::new();
+        // 当前长度下所有有希望被接受的 (状态, 串)
+        let mut level = vec![(initial_state, Vec::new())];
+        for len in 0..=max_len {
+            let mut next_level = Vec::new();
+            for (state, word) in level {
+                if self.finite_states.contains(state) {
+                    words.push(word.clone());
+                }
+                for input in &inputs {
+                    let to_state = match self.next_state(state, input) {
+                        Some(to_state) if distances.get(to_state).is_some_and(|d| len + 1 + d <= max_len) => to_state,
+                        _ => continue,
+                    };
+                    let mut next_word = word.clone();
+                    next_word.push(input.representative());
+                    next_level.push((to_state, next_word));
+                }
+            }
+            level = next_level;
+        }
+        words
+    }
+
+    /// 返回长度为 max_len + 1 的 `Vec`，下标 k 处为当前 DFA 接受的长度恰为 k 的串的个数，结果超出 `u64` 时取 `u64::MAX`
+    /// 按长度逐层统计从初态出发到达每个状态的串的个数，不需要枚举语言。字符区间按其包含的字符数计数，通配符与取反字符类视为无穷多个
+    pub fn count_accepted_by_length(&self, max_len: usize) -> Vec<u64> {
+        let mut counts = vec![0; max_len + 1];
+        let initial_state = match &self.initial_state {
+            Some(s) => s,
+            None => return counts,
+        };
+        let width = |input: &Input| match input {
+            Input::Char(_) => 1,
+            Input::Range(start, end) => (*end as u64 + 1).saturating_sub(*start as u64),
+            Input::Any | Input::Not { .. } => u64::MAX,
+        };
+        // 当前长度下，从初态出发到达每个状态的串的个数
+        let mut current = BTreeMap::from([(initial_state, 1u64)]);
+        for count in counts.iter_mut() {
+            *count = current.iter()
+                .filter(|(s, _)| self.finite_states.contains(**s))
+                .fold(0u64, |sum, (_, n)| sum.saturating_add(*n));
+            let mut next: BTreeMap<&State, u64> = BTreeMap::new();
+            for (from_state, n) in &current {
+                for (to_state, edge) in self.adjacency_matrix.get(*from_state).into_iter().flatten() {
+                    let edge_width = edge.input_set.iter().map(width).fold(0u64, u64::saturating_add);
+                    let entry = next.entry(to_state).or_default();
+                    *entry = entry.saturating_add(n.saturating_mul(edge_width));
+                }
+            }
+            current = next;
+        }
+        counts
+    }
+
+    /// 判断当前 DFA 接受的语言是否为空，即从初态出发无法到达任何终态
+    pub fn is_empty(&self) -> bool {
+        self.reachable_states().is_disjoint(&self.finite_states)
+    }
+
+    /// 返回当前 DFA 所接受语言的 Myhill–Nerode 指数，即等价类的个数，也就是识别该语言的最小完全 DFA 的状态数
+    /// 删除不可达状态与死状态后最小化并计数，缺少转换时再计入所有死串所在的一类；语言为空时直接返回 1
+    pub fn nerode_index(&self) -> usize {
+        if self.is_empty() {
+            return 1;
+        }
+        let mut dfa = self.clone();
+        dfa.remove_unreachable();
+        dfa.remove_dead_states();
+        let mut dfa = dfa.minimize();
+        // 最小化只保留弧上出现的输入，死串所在的类要在原字母表上判断
+        dfa.feasible_inputs.extend(self.feasible_inputs.iter().cloned());
+        let states = dfa.get_all_states_iter().chain(dfa.initial_state.iter()).collect::<BTreeSet<_>>();
+        states.len() + usize::from(!dfa.is_complete())
+    }
+
+    /// 判断当前 DFA 接受的语言是否有穷
+    /// 只考虑既可达又是活状态的状态，语言无穷当且仅当这些状态之间存在环
+    pub fn is_finite(&self) -> bool {
+        let useful_states = self.reachable_states().intersection(&self.live_states()).cloned().collect::<BTreeSet<_>>();
+        // 0 表示未访问，1 表示在当前搜索路径上，2 表示已完成搜索
+        let mut colors = BTreeMap::new();
+        fn has_cycle<'s>(dfa: &'s DFA, s: &'s State, useful_states: &BTreeSet<State>,
+                         colors: &mut BTreeMap<&'s State, u8>) -> bool {
+            colors.insert(s, 1);
+            for to_state in dfa.adjacency_matrix.get(s).into_iter().flat_map(|map| map.keys()) {
+                if !useful_states.contains(to_state) {
+                    continue;
+                }
+                match colors.get(to_state) {
+                    Some(1) => return true,
+                    Some(_) => {},
+                    None => if has_cycle(dfa, to_state, useful_states, colors) {
+                        return true;
+                    },
+                }
+            }
+            colors.insert(s, 2);
+            false
+        }
+        !useful_states.iter().any(|s| !colors.contains_key(s) && has_cycle(self, s, &useful_states, &mut colors))
+    }
+
+    /// 将当前 DFA 转换为正则表达式，使用状态消去法
+    /// 空串写作 `()`，元字符以及 `.`、`[` 使用 `\` 转义。弧上的输入都是单个字符时，结果能被 [`crate::regex::from_regex`] 解析回等价的 NFA；
+    /// 否则字符区间写作 `[a-z]`，通配符写作 `.`，取反字符类写作 `[^abc]` 或 `<^if,else>`，由多个字符组成的输入字符加上括号写作 `(if)`，
+    /// 这些写法不被 [`crate::regex::from_regex`] 支持 (`(if)` 会被解析为 i 与 f 的连接)，仅供阅读。DFA 不接受任何串时，返回 `∅`
+    pub fn to_regex(&self) -> String {
+        // 状态编号：原状态依次为 0..n，n 为新增的初态，n + 1 为新增的终态
+        let mut states = self.get_all_states_iter().collect::<BTreeSet<_>>();
+        states.extend(self.initial_state.iter());
+        states.extend(self.finite_states.iter());
+        let states = states.into_iter().collect::<Vec<_>>();
+        let index_of = states.iter().enumerate().map(|(i, s)| (*s, i)).collect::<BTreeMap<_, _>>();
+        let (start, accept) = (states.len(), states.len() + 1);
+
+        // 广义转换图，弧上的标记为正则表达式，空字符串表示 ɛ
+        let mut labels: BTreeMap<(usize, usize), String> = BTreeMap::new();
+        if let Some(initial_state) = &self.initial_state {
+            labels.insert((start, index_of[initial_state]), String::new());
+        }
+        for finite_state in &self.finite_states {
+            labels.insert((index_of[finite_state], accept), String::new());
+        }
+        for (from_state, to_map) in &self.adjacency_matrix {
+            for (to_state, edge) in to_map {
+                let label = edge.input_set.iter()
+                    .map(regex_symbol)
+                    .reduce(|r1, r2| regex_union(&r1, &r2))
+                    .unwrap_or_default();
+                labels.insert((index_of[from_state], index_of[to_state]), label);
+            }
+        }
+
+        // 逐个消去原状态 k，对每一对 i -> k -> j 将 R(i,k) R(k,k)* R(k,j) 并入 R(i,j)
+        for k in 0..states.len() {
+            let loop_label = labels.remove(&(k, k)).map(|r| regex_star(&r)).unwrap_or_default();
+            let incoming = labels.iter().filter(|((_, j), _)| *j == k).map(|((i, _), r)| (*i, r.clone())).collect::<Vec<_>>();
+            let outgoing = labels.iter().filter(|((i, _), _)| *i == k).map(|((_, j), r)| (*j, r.clone())).collect::<Vec<_>>();
+            labels.retain(|(i, j), _| *i != k && *j != k);
+            for (i, r_ik) in &incoming {
+                for (j, r_kj) in &outgoing {
+                    let label = regex_concat(&regex_concat(r_ik, &loop_label), r_kj);
+                    labels.entry((*i, *j))
+                        .and_modify(|r| *r = regex_union(r, &label))
+                        .or_insert(label);
+                }
+            }
+        }
+
+        match labels.remove(&(start, accept)) {
+            Some(r) if r.is_empty() => "()".to_string(),
+            Some(r) => r,
+            None => "∅".to_string(),
+        }
+    }
+
+    /// 构造一个 NFA，接受所有含有当前 DFA 所接受的串作为子串的串，即非锚定的搜索
+    /// 在当前 DFA 前后各连接一个可以跳过任意输入字符的状态，它在所有单个字符与 `feasible_inputs` 中的每个输入上都有自环
+    pub fn substring_matcher(&self) -> NFA {
+        let mut any = NFA::new();
+        any.set_case_insensitive(self.case_insensitive);
+        any.add_initial_states(once("0")).unwrap();
+        any.add_finite_states(once("0")).unwrap();
+        any.add_transfer_input("0", Input::range('\0', char::MAX), "0").unwrap();
+        for input in &self.feasible_inputs {
+            any.add_transfer_input("0", input.clone(), "0").unwrap();
+        }
+        any.clone().concat(NFA::from(self.clone())).concat(any)
+    }
+
+    /// 求当前 DFA 的反转，得到的 NFA 接受且仅接受当前 DFA 所接受的串的逆序
+    /// 所有弧反向，原终态成为新的初态，原初态成为唯一的终态，`feasible_inputs` 保持不变
+    pub fn reverse(&self) -> NFA {
+        let mut nfa = NFA::new();
+        nfa.set_case_insensitive(self.case_insensitive);
+        nfa.add_initial_states(self.finite_states.iter().map(|s| s.state_id.as_str())).unwrap();
+        nfa.add_finite_states(self.initial_state.iter().map(|s| s.state_id.as_str())).unwrap();
+        for (from_state, input, to_state) in self.transfer_rules() {
+            nfa.add_transfer_input(&to_state.state_id, input.clone(), &from_state.state_id).unwrap();
+        }
+        nfa.feasible_inputs.extend(self.feasible_inputs.iter().cloned());
+        nfa
+    }
+
+    /// 最小化当前 DFA，使用 Brzozowski 算法：反转、确定化、再反转、再确定化
+    pub fn minimize_brzozowski(&self) -> DFA {
+        // 反转得到的 NFA 没有初态时 (即原 DFA 没有终态)，语言为空，最小 DFA 只有一个非终态的初态
+        let determinize = |mut nfa: NFA| {
+            if nfa.initial_states.is_empty() {
+                let mut dfa = DFA::new();
+                dfa.add_initial_states(once("0")).unwrap();
+                dfa.feasible_inputs = nfa.feasible_inputs;
+                return dfa;
+            }
+            // 已经计算了 ɛ 闭包矩阵且初态集非空，因此 unwrap 不会失败
+            nfa.calc_epsilon_closure_matrix();
+            nfa.to_dfa().unwrap()
+        };
+        determinize(determinize(self.reverse()).reverse())
+    }
+
+    /// 判断当前 DFA 接受的语言是否包含于 other 接受的语言，即 `self ∩ ¬other` 是否为空
+    /// other 的补在两者字母表的并上计算，因此含有 other 字母表以外的输入字符的串也会被考虑
+    pub fn is_subset_of(&self, other: &DFA) -> bool {
+        self.difference(other).is_empty()
+    }
+
+    /// 求当前 DFA 与 other 的差，得到的 DFA 接受且仅接受被当前 DFA 接受而不被 other 接受的串，即 `self ∩ ¬other`
+    /// other 的补在两者字母表的并上计算，因此含有 other 字母表以外的输入字符的串也会被保留
+    pub fn difference(&self, other: &DFA) -> DFA {
+        let mut other = other.clone();
+        other.feasible_inputs.extend(self.feasible_inputs.iter().cloned());
+        self.intersect(&other.complement())
+    }
+
+    /// 构造当前 DFA 与 other 在字母表 alphabet 上的积自动机，alphabet 中的输入应互不相交，积状态的 id 为两个分量的 id 以 `|` 连接，
+    /// 分量 id 中的 `\` 与 `|` 以 `\` 转义，因此不同的积状态不会得到相同的 id
+    /// 某一分量缺少转换时，该分量进入隐式的陷阱状态；keep_partial 为 `false` 时，直接丢弃这样的转换
+    /// 积状态是否为终态由 is_final(第一分量是否为终态, 第二分量是否为终态) 决定
+    /// 两者都忽略大小写时，积自动机也忽略大小写；否则按区分大小写处理
+    fn product<F>(&self, other: &DFA, alphabet: BTreeSet<Input>, keep_partial: bool, is_final: F) -> DFA
+        where F: Fn(bool, bool) -> bool {
+        let self_trap_state_id = self.fresh_state_id("trap");
+        let other_trap_state_id = other.fresh_state_id("trap");
+        let escape = |id: &str| id.replace('\\', "\\\\").replace('|', "\\|");
+        let pair_id = |(p, q): (Option<&State>, Option<&State>)| format!("{}|{}",
+            escape(p.map_or(self_trap_state_id.as_str(), |s| s.state_id.as_str())),
+            escape(q.map_or(other_trap_state_id.as_str(), |s| s.state_id.as_str())));
+        // 两个分量都进入陷阱状态 (或 keep_partial 为 `false` 时任一分量进入陷阱状态) 的积状态不可能被接受
+        let is_dead = |(p, q): (Option<&State>, Option<&State>)| {
+            (p.is_none() && q.is_none()) || (!keep_partial && (p.is_none() || q.is_none()))
+        };
+
+        let mut dfa = DFA::new();
+        let case_insensitive = self.case_insensitive && other.case_insensitive;
+        let start_pair = (self.initial_state.as_ref(), other.initial_state.as_ref());
+        if is_dead(start_pair) {
+            dfa.set_case_insensitive(case_insensitive);
+            return dfa;
+        }
+        let mut search_queue = VecDeque::from([start_pair]); // 搜索队列
+        let mut known_pairs = BTreeSet::from([start_pair]); // 保存所有已知的积状态
+        dfa.add_initial_states(once(pair_id(start_pair).as_str())).unwrap();
+        while let Some(front_pair) = search_queue.pop_front() {
+            let front_pair_id = pair_id(front_pair);
+            let (p, q) = front_pair;
+            if is_final(p.is_some_and(|s| self.finite_states.contains(s)),
+                        q.is_some_and(|s| other.finite_states.contains(s))) {
+                dfa.add_finite_states(once(front_pair_id.as_str())).unwrap();
+            }
+            for input in &alphabet {
+                let transfered_pair = (p.and_then(|s| self.next_state(s, input)),
+                                       q.and_then(|s| other.next_state(s, input)));
+                if is_dead(transfered_pair) {
+                    continue;
+                }
+                if known_pairs.insert(transfered_pair) {
+                    search_queue.push_back(transfered_pair);
+                }
+                dfa.add_transfer_input(&front_pair_id, input.clone(), &pair_id(transfered_pair)).unwrap();
+            }
+        }
+        // 规则已按原样加入，最后再设置标志，避免输入被转为小写后产生冲突
+        dfa.set_case_insensitive(case_insensitive);
+        dfa
+    }
+
+    /// 求当前 DFA 与 other 的交，得到的 DFA 接受且仅接受两者都接受的串
+    /// 两者都忽略大小写时结果也忽略大小写，[`DFA::union`]、[`DFA::symmetric_difference`] 与 [`DFA::difference`] 同理
+    pub fn intersect(&self, other: &DFA) -> DFA {
+        let alphabet = input::alphabet_classes(self.feasible_inputs.iter().chain(&other.feasible_inputs));
+        self.product(other, alphabet, false, |f1, f2| f1 && f2)
+    }
+
+    /// 求当前 DFA 与 other 的并，得到的 DFA 接受且仅接受两者之一接受的串
+    pub fn union(&self, other: &DFA) -> DFA {
+        let alphabet = input::alphabet_classes(self.feasible_inputs.iter().chain(&other.feasible_inputs));
+        self.product(other, alphabet, true, |f1, f2| f1 || f2)
+    }
+
+    /// 求当前 DFA 与 other 的对称差，得到的 DFA 接受且仅接受恰好被其中一个接受的串
+    /// 配合 [`DFA::shortest_accepted`] 可以得到一个区分两者的串
+    pub fn symmetric_difference(&self, other: &DFA) -> DFA {
+        let alphabet = input::alphabet_classes(self.feasible_inputs.iter().chain(&other.feasible_inputs));
+        self.product(other, alphabet, true, |f1, f2| f1 != f2)
+    }
+}
+
+impl Debug for DFA {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        display::fmt_automaton(f, "initial_state", &self.initial_state, &self.finite_states, &self.adjacency_matrix)
+    }
+}
+
+impl Display for DFA {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        display::fmt_automaton(f, "initial_state", &self.initial_state, &self.finite_states, &self.adjacency_matrix)
+    }
+}
+
+/// DFA 的构造器，以链式调用的方式描述初态、终态与转换规则，最后由 [`DfaBuilder::build`] 生成 DFA
+#[derive(Default)]
+pub struct DfaBuilder {
+    initial_states: Vec<String>,
+    finite_states: Vec<String>,
+    rules: Vec<(String, String, String)>,
+}
+
+impl DfaBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 指定初态，DFA 有且仅有一个初态
+    pub fn initial(mut self, state_id: &str) -> Self {
+        self.initial_states.push(state_id.to_string());
+        self
+    }
+
+    /// 添加一个终态
+    pub fn finite(mut self, state_id: &str) -> Self {
+        self.finite_states.push(state_id.to_string());
+        self
+    }
+
+    /// 添加一条转换规则
+    pub fn rule(mut self, from_state_id: &str, input_str: &str, to_state_id: &str) -> Self {
+        self.rules.push((from_state_id.to_string(), input_str.to_string(), to_state_id.to_string()));
+        self
+    }
+
+    /// 生成 DFA，初态不唯一时抛出 [`Error::IllegalArgument`]，
+    /// 含有 ɛ 转换或不确定的转换时抛出与 [`DFA::add_transfer_rule`] 相同的错误
+    pub fn build(self) -> IResult<DFA> {
+        let mut dfa = DFA::new();
+        dfa.add_initial_states(self.initial_states.iter().map(String::as_str))?;
+        dfa.add_finite_states(self.finite_states.iter().map(String::as_str))?;
+        for (from_state_id, input_str, to_state_id) in &self.rules {
+            dfa.add_transfer_rule(from_state_id, input_str, to_state_id)?;
+        }
+        Ok(dfa)
+    }
+}
+
+/// 以声明式的语法构造 DFA，返回 `IResult<DFA>`，初态不唯一或转换不确定时返回相应的错误
+/// ```
+/// use automata::dfa;
+/// let dfa = dfa!(
+///     initial_state: "0";
+///     finite_states: "1";
+///     transfer_rules: "0" => "a" => "1",
+///                     "1" => "a" => "0"
+/// ).unwrap();
+/// assert!(dfa.accepts(&["a", "a", "a"]));
+/// ```
+#[macro_export]
+macro_rules! dfa {
+    (initial_state: $initial_state: expr ;
+     finite_states: $($finite_state: expr),* ;
+     transfer_rules: $($from_state: expr => $input: expr => $to_state: expr),*) => {{
+        use $crate::automaton::FiniteAutomaton;
+        (|| -> $crate::result::IResult<$crate::dfa::DFA> {
+            let mut dfa = $crate::dfa::DFA::new();
+            dfa.add_initial_states(::std::iter::once($initial_state))?;
+            $(dfa.add_finite_states(::std::iter::once($finite_state))?;)*
+            $(dfa.add_transfer_rule($from_state, $input, $to_state)?;)*
+            Ok(dfa)
+        })()
+    }};
+}
+
+/// SplitMix64 伪随机数生成器，每次调用推进 state 并返回下一个随机数
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E3779B97F4A7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/// 将一个输入写为正则表达式，转义元字符以及 `.`、`[`，多个字符组成的输入字符加上括号，
+/// 字符区间、通配符与取反字符类按 [`Input`] 的 `Display` 写出，与转义后的字符不会混淆
+fn regex_symbol(input: &Input) -> String {
+    let input_str = match input {
+        Input::Char(s) => s,
+        range => return range.to_string(),
+    };
+    let escaped = input_str.chars()
+        .map(|c| if "|*()\\.[".contains(c) { format!("\\{}", c) } else { c.to_string() })
+        .collect::<String>();
+    if input_str.chars().count() == 1 { escaped } else { format!("({})", escaped) }
+}
+
+/// 判断正则表达式 r 能否直接作为闭包或连接的操作数，即 r 为单个 (可能被转义的) 字符、字符区间、取反字符类或整体被一对括号包围
+fn regex_is_atomic(r: &str) -> bool {
+    let chars = r.chars().collect::<Vec<_>>();
+    match chars.as_slice() {
+        [_] | ['\\', _] | ['[', _, '-', _, ']'] => true,
+        // 取反字符类中的输入字符不含 `]` 时，第一个 `]` 即为末尾
+        ['[', '^', rest @ .., ']'] => !rest.contains(&']'),
+        ['(', .., ')'] => {
+            // 检查第一个左括号是否与最后一个右括号匹配
+            let mut depth = 0;
+            let mut escaped = false;
+            for (i, c) in chars.iter().enumerate() {
+                match (escaped, c) {
+                    (true, _) => escaped = false,
+                    (false, '\\') => escaped = true,
+                    (false, '(') => depth += 1,
+                    (false, ')') => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return i == chars.len() - 1;
+                        }
+                    },
+                    _ => {},
+                }
+            }
+            false
+        },
+        _ => false,
+    }
+}
+
+/// 正则表达式的并，空字符串表示 ɛ
+fn regex_union(r1: &str, r2: &str) -> String {
+    if r1 == r2 { r1.to_string() } else { format!("({}|{})", r1, r2) }
+}
+
+/// 正则表达式的连接，空字符串表示 ɛ
+fn regex_concat(r1: &str, r2: &str) -> String {
+    let wrap = |r: &str| if r.contains('|') && !regex_is_atomic(r) { format!("({})", r) } else { r.to_string() };
+    format!("{}{}", wrap(r1), wrap(r2))
+}
+
+/// 正则表达式的闭包，空字符串表示 ɛ
+fn regex_star(r: &str) -> String {
+    if r.is_empty() {
+        String::new()
+    } else if regex_is_atomic(r) {
+        format!("{}*", r)
+    } else {
+        format!("({})*", r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn new_test() {
+        let mut dfa = DFA::new();
+        assert_eq!(dfa.get_states_num(), 0);
+        assert_eq!(dfa.initial_state, None);
+        dfa.add_initial_states(once("0")).unwrap();
+        dfa.add_transfer_rule("0", "a", "1").unwrap();
+        dfa.add_transfer_rule("1", "b", "0").unwrap();
+        assert_eq!(dfa.get_states_num(), 2);
+        assert_eq!(dfa.initial_state, Some(State::new("0")));
+    }
+
+    #[test]
+    fn add_transfer_rule_test() {
+        let mut dfa = DFA::new();
+        dfa.add_transfer_rule("0", "a", "1").unwrap();
+        // 重复添加同一规则不会出错
+        dfa.add_transfer_rule("0", "a", "1").unwrap();
+        assert!(matches!(dfa.add_transfer_rule("0", "a", "2"), Err(Error::UnsupportedOperation(_))));
+        assert!(matches!(dfa.add_transfer_rule("0", "ɛ", "1"), Err(Error::IllegalArgument(_))));
+        assert_eq!(dfa.get_states_num(), 2);
+        assert!(!dfa.feasible_inputs.contains(&Input::new("ɛ")));
+    }
+
+    /// 接受所有以 "ab" 结尾的 {a, b} 串
+    fn ends_with_ab() -> DFA {
+        let mut dfa = DFA::new();
+        dfa.add_initial_states(once("0")).unwrap();
+        dfa.add_finite_states(once("2")).unwrap();
+        dfa.add_transfer_rule("0", "a", "1").unwrap();
+        dfa.add_transfer_rule("0", "b", "0").unwrap();
+        dfa.add_transfer_rule("1", "a", "1").unwrap();
+        dfa.add_transfer_rule("1", "b", "2").unwrap();
+        dfa.add_transfer_rule("2", "a", "1").unwrap();
+        dfa.add_transfer_rule("2", "b", "0").unwrap();
+        dfa
+    }
+
+    #[test]
+    fn accepts_test() {
+        let dfa = ends_with_ab();
+        assert!(dfa.accepts(&["a", "b"]));
+        assert!(dfa.accepts(&["b", "a", "a", "b"]));
+        assert!(!dfa.accepts(&[]));
+        assert!(!dfa.accepts(&["a", "b", "a"]));
+        assert!(!dfa.accepts(&["a", "c"]));
+        assert!(!DFA::new().accepts(&[]));
+    }
+
+    #[test]
+    fn minimize_test() {
+        // 接受所有以 a 结尾的 {a, b} 串，其中 2 与 1 等价，3 与 0 等价
+        let mut dfa = DFA::new();
+        dfa.add_initial_states(once("0")).unwrap();
+        dfa.add_finite_states(["1", "2"].into_iter()).unwrap();
+        dfa.add_transfer_rule("0", "a", "1").unwrap();
+        dfa.add_transfer_rule("0", "b", "3").unwrap();
+        dfa.add_transfer_rule("1", "a", "2").unwrap();
+        dfa.add_transfer_rule("1", "b", "0").unwrap();
+        dfa.add_transfer_rule("2", "a", "1").unwrap();
+        dfa.add_transfer_rule("2", "b", "3").unwrap();
+        dfa.add_transfer_rule("3", "a", "2").unwrap();
+        dfa.add_transfer_rule("3", "b", "0").unwrap();
+        let minimized = dfa.minimize();
+        assert_eq!(minimized.get_states_num(), 2);
+        assert_eq!(minimized.finite_states.len(), 1);
+        for input in [&[][..], &["a"], &["b"], &["b", "a"], &["a", "b"], &["a", "a", "b", "a"]] {
+            assert_eq!(minimized.accepts(input), dfa.accepts(input));
+        }
+        // 已经最小的 DFA 保持不变
+        assert_eq!(ends_with_ab().minimize().get_states_num(), 3);
+    }
+
+    /// 返回 alphabet 上所有长度不超过 max_len 的串
+    fn all_words<'a>(alphabet: &[&'a str], max_len: usize) -> Vec<Vec<&'a str>> {
+        let mut words = vec![vec![]];
+        let mut last = vec![vec![]];
+        for _ in 0..max_len {
+            last = last.iter()
+                .flat_map(|w: &Vec<&'a str>| alphabet.iter().map(move |c| [w.as_slice(), &[*c]].concat()))
+                .collect();
+            words.extend(last.iter().cloned());
+        }
+        words
+    }
+
+    #[test]
+    fn declare_alphabet_test() {
+        let mut dfa = ends_with_ab();
+        dfa.declare_alphabet(["c", "ɛ"].into_iter());
+        assert_eq!(dfa.feasible_inputs, BTreeSet::from(["a", "b", "c"].map(Input::new)));
+        assert_eq!(dfa.num_edges(), 6);
+        assert!(!dfa.is_complete());
+        dfa.complete();
+        assert!(dfa.is_complete());
+        assert_eq!(dfa.get_states_num(), 4);
+        assert!(!dfa.accepts(&["a", "b", "c"]));
+        // 补也会接受含有已声明但未使用的输入字符的串
+        let complement = ends_with_ab().complement();
+        assert!(!complement.accepts(&["a", "c"]));
+        let mut declared = ends_with_ab();
+        declared.declare_alphabet(once("c"));
+        assert!(declared.complement().accepts(&["a", "c"]));
+    }
+
+    #[test]
+    fn complement_test() {
+        for dfa in [only_ab(), ends_with_ab()] {
+            let complement = dfa.complement();
+            for word in all_words(&["a", "b"], 4) {
+                assert_ne!(dfa.accepts(&word), complement.accepts(&word), "{:?}", word);
+            }
+        }
+        // 已经完全的 DFA 不需要陷阱状态
+        assert_eq!(ends_with_ab().complement().get_states_num(), 3);
+        assert!(DFA::new().complement().accepts(&[]));
+    }
+
+    #[test]
+    fn complete_test() {
+        let mut dfa = only_ab();
+        assert!(!dfa.is_complete());
+        dfa.complete();
+        assert!(dfa.is_complete());
+        assert_eq!(dfa.get_states_num(), 4);
+        for word in all_words(&["a", "b"], 4) {
+            assert_eq!(dfa.accepts(&word), only_ab().accepts(&word), "{:?}", word);
+        }
+        // 已经完全的 DFA 保持不变
+        let mut dfa = ends_with_ab();
+        assert!(dfa.is_complete());
+        dfa.complete();
+        assert_eq!(dfa.get_states_num(), 3);
+    }
+
+    #[test]
+    fn trap_states_test() {
+        let mut dfa = only_ab();
+        assert!(dfa.trap_states().is_empty());
+        dfa.complete();
+        assert_eq!(dfa.trap_states(), BTreeSet::from([State::new("trap")]));
+        // 完全但不含陷阱状态的 DFA
+        assert!(even_a().complement().trap_states().is_empty());
+    }
+
+    /// 接受所有含偶数个 a 的 {a, b} 串
+    fn even_a() -> DFA {
+        let mut dfa = DFA::new();
+        dfa.add_initial_states(once("e")).unwrap();
+        dfa.add_finite_states(once("e")).unwrap();
+        dfa.add_transfer_rule("e", "a", "o").unwrap();
+        dfa.add_transfer_rule("e", "b", "e").unwrap();
+        dfa.add_transfer_rule("o", "a", "e").unwrap();
+        dfa.add_transfer_rule("o", "b", "o").unwrap();
+        dfa
+    }
+
+    /// 只接受 "ab"
+    fn only_ab() -> DFA {
+        let mut dfa = DFA::new();
+        dfa.add_initial_states(once("0")).unwrap();
+        dfa.add_finite_states(once("2")).unwrap();
+        dfa.add_transfer_rule("0", "a", "1").unwrap();
+        dfa.add_transfer_rule("1", "b", "2").unwrap();
+        dfa
+    }
+
+    #[test]
+    fn intersect_test() {
+        for (dfa1, dfa2) in [(even_a(), ends_with_ab()), (ends_with_ab(), only_ab()), (even_a(), only_ab())] {
+            let intersection = dfa1.intersect(&dfa2);
+            for word in all_words(&["a", "b"], 5) {
+                assert_eq!(intersection.accepts(&word), dfa1.accepts(&word) && dfa2.accepts(&word), "{:?}", word);
+            }
+        }
+        let intersection = even_a().intersect(&ends_with_ab());
+        assert!(intersection.accepts(&["a", "a", "a", "a", "b"]));
+        assert!(intersection.get_all_states_iter().all(|s| s.state_id.contains('|')));
+    }
+
+    #[test]
+    fn union_test() {
+        let mut c = DFA::new();
+        c.add_initial_states(once("0")).unwrap();
+        c.add_finite_states(once("1")).unwrap();
+        c.add_transfer_rule("0", "c", "1").unwrap();
+        for (dfa1, dfa2) in [(even_a(), ends_with_ab()), (only_ab(), c)] {
+            let union = dfa1.union(&dfa2);
+            for word in all_words(&["a", "b", "c"], 4) {
+                assert_eq!(union.accepts(&word), dfa1.accepts(&word) || dfa2.accepts(&word), "{:?}", word);
+            }
+        }
+    }
+
+    #[test]
+    fn remove_unreachable_test() {
+        let mut dfa = ends_with_ab();
+        dfa.add_finite_states(once("isolated")).unwrap();
+        dfa.add_transfer_rule("isolated", "a", "isolated").unwrap();
+        dfa.add_transfer_rule("isolated", "b", "0").unwrap();
+        assert_eq!(dfa.get_states_num(), 4);
+        dfa.remove_unreachable();
+        assert_eq!(dfa.get_states_num(), 3);
+        assert!(!dfa.finite_states.contains(&State::new("isolated")));
+        for word in all_words(&["a", "b"], 4) {
+            assert_eq!(dfa.accepts(&word), ends_with_ab().accepts(&word));
+        }
+
+        let mut dfa = ends_with_ab();
+        dfa.initial_state = None;
+        dfa.remove_unreachable();
+        assert_eq!(dfa.get_states_num(), 0);
+        assert!(dfa.finite_states.is_empty());
+    }
+
+    #[test]
+    fn remove_dead_states_test() {
+        let original = only_ab().complement().complement();
+        let mut dfa = only_ab().complement().complement();
+        assert!(dfa.adjacency_matrix.contains_key(&State::new("trap")));
+        dfa.remove_dead_states();
+        assert!(!dfa.adjacency_matrix.contains_key(&State::new("trap")));
+        assert_eq!(dfa.get_states_num(), 3);
+        for word in all_words(&["a", "b"], 4) {
+            assert_eq!(dfa.accepts(&word), original.accepts(&word));
+        }
+
+        // 初态本身是死状态时，删除所有状态但保留初态
+        let mut dfa = ends_with_ab();
+        dfa.finite_states.clear();
+        dfa.remove_dead_states();
+        assert_eq!(dfa.get_states_num(), 0);
+        assert_eq!(dfa.initial_state, Some(State::new("0")));
+    }
+
+    #[test]
+    fn shortest_accepted_test() {
+        assert_eq!(ends_with_ab().shortest_accepted(), Some(vec!["a".to_string(), "b".to_string()]));
+        assert_eq!(even_a().shortest_accepted(), Some(vec![]));
+        assert_eq!(even_a().intersect(&only_ab()).shortest_accepted(), None);
+        assert_eq!(DFA::new().shortest_accepted(), None);
+    }
+
+    #[test]
+    fn enumerate_language_test() {
+        let words = |words: &[&[&str]]| words.iter()
+            .map(|word| word.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(ends_with_ab().enumerate_language(3), words(&[&["a", "b"], &["a", "a", "b"], &["b", "a", "b"]]));
+        assert_eq!(even_a().enumerate_language(2), words(&[&[], &["b"], &["a", "a"], &["b", "b"]]));
+        assert_eq!(ends_with_ab().enumerate_language(1), words(&[]));
+        for word in ends_with_ab().enumerate_language(5) {
+            assert!(ends_with_ab().accepts(&word.iter().map(String::as_str).collect::<Vec<_>>()));
+        }
+        let expected = all_words(&["a", "b"], 5).into_iter().filter(|w| ends_with_ab().accepts(w)).count();
+        assert_eq!(ends_with_ab().enumerate_language(5).len(), expected);
+        assert!(DFA::new().enumerate_language(3).is_empty());
+    }
+
+    #[test]
+    fn accepts_tokens_test() {
+        // if ( id ) id，之后可以接任意多个 else if 分支
+        let dfa = DfaBuilder::new()
+            .initial("0")
+            .finite("5")
+            .rule("0", "if", "1")
+            .rule("1", "(", "2")
+            .rule("2", "id", "3")
+            .rule("3", ")", "4")
+            .rule("4", "id", "5")
+            .rule("5", "else", "0")
+            .build()
+            .unwrap();
+        assert!(dfa.accepts_tokens(&["if", "(", "id", ")", "id"]));
+        assert!(dfa.accepts_tokens(&["if", "(", "id", ")", "id", "else", "if", "(", "id", ")", "id"]));
+        assert!(!dfa.accepts_tokens(&["if", "(", "id", ")"]));
+        assert!(!dfa.accepts_tokens(&["i", "f", "(", "id", ")", "id"]));
+    }
+
+    #[test]
+    fn case_insensitive_test() {
+        let mut dfa = ends_with_ab();
+        assert!(!dfa.accepts(&["A", "B"]));
+        assert!(dfa.accepts_checked(&["A", "B"]).is_err());
+        dfa.set_case_insensitive(true);
+        assert!(dfa.accepts(&["A", "B"]));
+        assert!(dfa.accepts(&["b", "A", "b"]));
+        assert!(dfa.accepts_checked(&["A", "B"]).unwrap());
+        dfa.build_index();
+        assert!(dfa.accepts_fast(&["a", "B"]));
+        assert!(dfa.minimize().accepts(&["A", "B"]));
+        assert!(!dfa.complement().accepts(&["A", "B"]));
+
+        // 交、并与差保留忽略大小写的语义，只有一方忽略大小写时按区分大小写处理
+        let mut a = dfa!(
+            initial_state: "0";
+            finite_states: "1";
+            transfer_rules: "0" => "a" => "1").unwrap();
+        a.set_case_insensitive(true);
+        assert!(a.accepts(&["A"]));
+        assert!(a.intersect(&a).accepts(&["A"]));
+        assert!(a.union(&a).accepts(&["A"]));
+        assert!(a.is_subset_of(&a));
+        assert!(a.symmetric_difference(&a).is_empty());
+        assert!(!a.difference(&a).accepts(&["A"]));
+        assert!(a.intersect(&dfa).is_empty());
+        let mut sensitive = a.clone();
+        sensitive.set_case_insensitive(false);
+        assert!(!a.intersect(&sensitive).accepts(&["A"]));
+        assert!(a.intersect(&sensitive).accepts(&["a"]));
+    }
+
+    #[test]
+    fn sample_accepted_test() {
+        let dfa = ends_with_ab();
+        assert_eq!(dfa.sample_accepted(6, 42), dfa.sample_accepted(6, 42));
+        let samples = (0..32).filter_map(|seed| dfa.sample_accepted(6, seed)).collect::<Vec<_>>();
+        assert!(!samples.is_empty());
+        for word in samples {
+            assert!(word.len() <= 6);
+            assert!(dfa.accepts(&word.iter().map(String::as_str).collect::<Vec<_>>()));
+        }
+        // 走 0 步时停在初态，初态不是终态
+        assert_eq!(dfa.sample_accepted(0, 0), None);
+        assert_eq!(DFA::new().sample_accepted(5, 0), None);
+    }
+
+    #[test]
+    fn distinguishable_pairs_test() {
+        // 1 与 2 等价，其余状态两两可区分
+        let mut dfa = DFA::new();
+        dfa.add_initial_states(once("0")).unwrap();
+        dfa.add_finite_states(once("3")).unwrap();
+        dfa.add_transfer_rule("0", "a", "1").unwrap();
+        dfa.add_transfer_rule("0", "b", "2").unwrap();
+        dfa.add_transfer_rule("1", "a", "3").unwrap();
+        dfa.add_transfer_rule("2", "a", "3").unwrap();
+        let pair = |p: &str, q: &str| (State::new(p), State::new(q));
+        assert_eq!(dfa.distinguishable_pairs(), BTreeSet::from([
+            pair("0", "1"), pair("0", "2"), pair("0", "3"), pair("1", "3"), pair("2", "3"),
+        ]));
+        assert_eq!(dfa.minimize().get_states_num(), 3);
+        assert!(DFA::new().distinguishable_pairs().is_empty());
+    }
+
+    #[test]
+    fn count_accepted_by_length_test() {
+        let mut all = DFA::new();
+        all.add_initial_states(once("0")).unwrap();
+        all.add_finite_states(once("0")).unwrap();
+        all.add_transfer_rule("0", "a", "0").unwrap();
+        all.add_transfer_rule("0", "b", "0").unwrap();
+        assert_eq!(all.count_accepted_by_length(5), vec![1, 2, 4, 8, 16, 32]);
+        assert_eq!(all.count_accepted_by_length(70)[63], 1 << 63);
+        assert_eq!(all.count_accepted_by_length(70)[70], u64::MAX);
+        assert_eq!(ends_with_ab().count_accepted_by_length(4), vec![0, 0, 1, 2, 4]);
+        assert_eq!(only_ab().count_accepted_by_length(3), vec![0, 0, 1, 0]);
+        assert_eq!(DFA::new().count_accepted_by_length(2), vec![0, 0, 0]);
+        let mut digits = DFA::new();
+        digits.add_initial_states(once("0")).unwrap();
+        digits.add_finite_states(once("1")).unwrap();
+        digits.add_transfer_input("0", Input::range('0', '9'), "1").unwrap();
+        assert_eq!(digits.count_accepted_by_length(2), vec![0, 10, 0]);
+    }
+
+    #[test]
+    fn run_test() {
+        let dfa = ends_with_ab();
+        assert_eq!(dfa.run(&[]), Some(State::new("0")));
+        assert_eq!(dfa.run(&["b", "a"]), Some(State::new("1")));
+        assert_eq!(dfa.run(&["a", "b"]), Some(State::new("2")));
+        assert_eq!(only_ab().run(&["b"]), None);
+        assert_eq!(DFA::new().run(&[]), None);
+        for word in all_words(&["a", "b"], 4) {
+            assert_eq!(dfa.accepts(&word), dfa.run(&word).is_some_and(|s| dfa.finite_states.contains(&s)));
+        }
+    }
+
+    #[test]
+    fn longest_accepted_prefix_test() {
+        let dfa = ends_with_ab();
+        assert_eq!(dfa.longest_accepted_prefix(&["a", "b", "b", "a", "b", "a"]), Some(5));
+        assert_eq!(dfa.longest_accepted_prefix(&["a", "b"]), Some(2));
+        assert_eq!(dfa.longest_accepted_prefix(&["b", "a", "a"]), None);
+        // 卡住后不再继续读入
+        assert_eq!(only_ab().longest_accepted_prefix(&["a", "b", "a", "b"]), Some(2));
+        assert_eq!(only_ab().longest_accepted_prefix(&["b", "a", "b"]), None);
+        assert_eq!(even_a().longest_accepted_prefix(&["a"]), Some(0));
+        assert_eq!(DFA::new().longest_accepted_prefix(&["a"]), None);
+    }
+
+    #[test]
+    fn find_test() {
+        let dfa = only_ab();
+        assert!(!dfa.accepts(&["b", "b", "a", "b", "a"]));
+        assert_eq!(dfa.find(&["b", "b", "a", "b", "a"]), Some((2, 4)));
+        assert_eq!(dfa.find(&["a", "b"]), Some((0, 2)));
+        assert_eq!(dfa.find(&["b", "a", "a"]), None);
+        // 取第一个起点上的最长匹配
+        assert_eq!(ends_with_ab().find(&["b", "a", "b", "a", "b"]), Some((0, 5)));
+        assert_eq!(even_a().find(&["a"]), Some((0, 0)));
+    }
+
+    #[test]
+    fn to_dense_table_test() {
+        let dfa = ends_with_ab();
+        let (states, inputs, table) = dfa.to_dense_table();
+        assert_eq!(states, vec!["0", "1", "2"]);
+        assert_eq!(inputs, vec!["a", "b"]);
+        assert_eq!(table.len(), 3);
+        let initial = states.iter().position(|s| s == "0").unwrap();
+        for word in all_words(&["a", "b"], 4) {
+            let mut current = Some(initial);
+            for symbol in &word {
+                let column = inputs.iter().position(|i| i == symbol).unwrap();
+                current = current.and_then(|i| table[i][column]);
+            }
+            let accepted = current.is_some_and(|i| dfa.finite_states.contains(&State::new(states[i].as_str())));
+            assert_eq!(accepted, dfa.accepts(&word), "{:?}", word);
+        }
+        // 缺少的转换为 None
+        assert_eq!(only_ab().to_dense_table().2[0], vec![Some(1), None]);
+    }
+
+    #[test]
+    fn canonical_hash_test() {
+        let dfa = ends_with_ab();
+        // 状态 id 不同、多出等价状态、不可达状态与死状态的等价 DFA
+        let redundant = dfa!(
+            initial_state: "p";
+            finite_states: "r", "r2";
+            transfer_rules: "p" => "a" => "q",
+                            "p" => "b" => "p",
+                            "q" => "a" => "q",
+                            "q" => "b" => "r",
+                            "r" => "a" => "q2",
+                            "r" => "b" => "p",
+                            "q2" => "a" => "q2",
+                            "q2" => "b" => "r2",
+                            "r2" => "a" => "q",
+                            "r2" => "b" => "p",
+                            "unreachable" => "a" => "p",
+                            "p" => "c" => "dead").unwrap();
+        assert_ne!(redundant.get_states_num(), dfa.minimize().get_states_num());
+        assert_eq!(redundant.canonical_hash(), dfa.canonical_hash());
+        assert_ne!(only_ab().canonical_hash(), dfa.canonical_hash());
+
+        // 空语言
+        let empty = dfa!(
+            initial_state: "0";
+            finite_states: ;
+            transfer_rules: "0" => "a" => "1").unwrap();
+        assert_eq!(empty.canonical_hash(), DFA::new().canonical_hash());
+        assert_ne!(empty.canonical_hash(), dfa.canonical_hash());
+    }
+
+    #[test]
+    fn to_dsl_test() {
+        let dfa = ends_with_ab();
+        assert_eq!(dfa.to_dsl(), "initial: 0\nfinal: 2\n0 -b-> 0\n0 -a-> 1\n1 -a-> 1\n1 -b-> 2\n2 -b-> 0\n2 -a-> 1\n");
+        assert_eq!(NFA::from_dsl(&dfa.to_dsl()).unwrap().as_dfa().unwrap(), dfa);
+    }
+
+    #[test]
+    fn self_loop_on_missing_test() {
+        let mut dfa = only_ab();
+        assert!(!dfa.accepts(&["a", "c", "b"]));
+        assert!(!dfa.accepts(&["a", "b", "b"]));
+        dfa.set_self_loop_on_missing(true);
+        // 缺少的转换视为自环，同一个 DFA 接受更多的串
+        assert!(dfa.accepts(&["a", "c", "b"]));
+        assert!(dfa.accepts(&["a", "b", "b"]));
+        assert!(dfa.accepts(&["c", "a", "b", "a"]));
+        assert!(!dfa.accepts(&["b", "a"]));
+        assert_eq!(dfa.run(&["a", "a"]), Some(State::new("1")));
+        dfa.build_index();
+        assert!(dfa.accepts_fast(&["a", "c", "b"]));
+        for word in all_words(&["a", "b"], 4) {
+            assert!(dfa.accepts(&word) || !only_ab().accepts(&word));
+        }
+    }
+
+    #[test]
+    fn nerode_index_test() {
+        // 多出等价状态、不可达状态与死状态的 ends_with_ab
+        let bloated = dfa!(
+            initial_state: "p";
+            finite_states: "r", "r2";
+            transfer_rules: "p" => "a" => "q",
+                            "p" => "b" => "p",
+                            "q" => "a" => "q2",
+                            "q" => "b" => "r",
+                            "r" => "a" => "q",
+                            "r" => "b" => "p",
+                            "q2" => "a" => "q",
+                            "q2" => "b" => "r2",
+                            "r2" => "a" => "q2",
+                            "r2" => "b" => "p",
+                            "unreachable" => "a" => "p").unwrap();
+        assert_eq!(bloated.get_states_num(), 6);
+        assert_eq!(bloated.nerode_index(), 3);
+        assert_eq!(ends_with_ab().nerode_index(), 3);
+        // 不以 ab 开头的串都落入死状态这一类
+        assert_eq!(only_ab().nerode_index(), 4);
+        assert_eq!(DFA::new().nerode_index(), 1);
+        let mut dfa = only_ab();
+        dfa.finite_states.clear();
+        assert_eq!(dfa.nerode_index(), 1);
+        // 只接受空串的语言：没有任何弧的唯一状态，声明字母表后还有死串所在的一类
+        let mut epsilon_only = DFA::new();
+        epsilon_only.add_initial_states(once("0")).unwrap();
+        epsilon_only.add_finite_states(once("0")).unwrap();
+        assert_eq!(epsilon_only.nerode_index(), 1);
+        epsilon_only.declare_alphabet(once("a"));
+        assert_eq!(epsilon_only.nerode_index(), 2);
+    }
+
+    #[test]
+    fn transitions_from_test() {
+        assert_eq!(ends_with_ab().transitions_from("1"), vec![
+            (Input::new("a"), State::new("1")),
+            (Input::new("b"), State::new("2")),
+        ]);
+        assert!(only_ab().transitions_from("2").is_empty());
+        assert!(only_ab().transitions_from("3").is_empty());
+    }
+
+    #[test]
+    fn strongly_connected_components_test() {
+        // 1 与 2 构成一个环，0 与 3 各自单独成为一个分量
+        let mut dfa = DFA::new();
+        dfa.add_initial_states(once("0")).unwrap();
+        dfa.add_finite_states(once("3")).unwrap();
+        dfa.add_transfer_rule("0", "a", "1").unwrap();
+        dfa.add_transfer_rule("1", "a", "2").unwrap();
+        dfa.add_transfer_rule("2", "a", "1").unwrap();
+        dfa.add_transfer_rule("2", "b", "3").unwrap();
+        assert_eq!(dfa.strongly_connected_components(), vec![
+            BTreeSet::from([State::new("3")]),
+            BTreeSet::from([State::new("1"), State::new("2")]),
+            BTreeSet::from([State::new("0")]),
+        ]);
+        assert_eq!(NFA::from(dfa.clone()).strongly_connected_components(), dfa.strongly_connected_components());
+        assert_eq!(even_a().strongly_connected_components().len(), 1);
+    }
+
+    #[test]
+    fn rename_input_test() {
+        let mut dfa = ends_with_ab();
+        dfa.rename_input("a", "x").unwrap();
+        assert_eq!(dfa.feasible_inputs, BTreeSet::from([Input::new("b"), Input::new("x")]));
+        assert!(dfa.accepts(&["b", "x", "b"]));
+        assert!(!dfa.accepts(&["a", "b"]));
+        // 状态 0 经 x 与经 b 转换到不同的状态
+        assert!(matches!(dfa.rename_input("x", "b"), Err(Error::IllegalArgument(_))));
+        assert!(dfa.accepts(&["x", "b"]));
+        let mut dfa = only_ab();
+        dfa.rename_input("b", "a").unwrap();
+        assert!(dfa.accepts(&["a", "a"]));
+    }
+
+    #[test]
+    fn rename_state_test() {
+        let mut dfa = ends_with_ab();
+        dfa.rename_state("0", "start").unwrap();
+        dfa.rename_state("2", "end").unwrap();
+        assert_eq!(dfa.initial_state, Some(State::new("start")));
+        assert_eq!(dfa.finite_states, BTreeSet::from([State::new("end")]));
+        assert_eq!(dfa.run(&["b", "a", "b"]), Some(State::new("end")));
+        assert_eq!(dfa.run(&["a", "b", "b"]), Some(State::new("start")));
+        assert!(matches!(dfa.rename_state("1", "end"), Err(Error::IllegalArgument(_))));
+        assert!(matches!(dfa.rename_state("0", "zero"), Err(Error::StateNotFound(_))));
+    }
+
+    #[test]
+    fn prefix_closure_test() {
+        let dfa = only_ab().prefix_closure();
+        assert!(dfa.accepts(&[]));
+        assert!(dfa.accepts(&["a"]));
+        assert!(dfa.accepts(&["a", "b"]));
+        assert!(!dfa.accepts(&["b"]));
+        assert!(!dfa.accepts(&["a", "b", "a"]));
+        // 陷阱状态不是活状态，补全后的前缀闭包不会接受更多的串
+        let mut completed = only_ab();
+        completed.complete();
+        assert_eq!(completed.prefix_closure().enumerate_language(3), dfa.enumerate_language(3));
+    }
+
+    #[test]
+    fn substring_matcher_test() {
+        let matcher = only_ab().substring_matcher();
+        assert!(matcher.accepts(["x", "x", "a", "b", "y"].into_iter()).unwrap());
+        assert!(matcher.accepts(["a", "b"].into_iter()).unwrap());
+        assert!(!matcher.accepts(["x", "x", "a", "y", "b"].into_iter()).unwrap());
+        assert!(!matcher.accepts(std::iter::empty()).unwrap());
+    }
+
+    #[test]
+    fn derivative_test() {
+        let mut abc = DFA::new();
+        abc.add_initial_states(once("0")).unwrap();
+        abc.add_finite_states(once("3")).unwrap();
+        abc.add_transfer_rule("0", "a", "1").unwrap();
+        abc.add_transfer_rule("1", "b", "2").unwrap();
+        abc.add_transfer_rule("2", "c", "3").unwrap();
+        let derivative = abc.derivative(&["a"]);
+        assert_eq!(derivative.enumerate_language(4), vec![vec!["b".to_string(), "c".to_string()]]);
+        assert_eq!(derivative.get_states_num(), 3);
+        assert_eq!(abc.derivative(&[]), abc);
+        let stuck = abc.derivative(&["b"]);
+        assert!(stuck.is_empty());
+        assert_eq!(stuck.get_states_num(), 0);
+    }
+
+    #[test]
+    fn accepts_all_test() {
+        let mut dfa = ends_with_ab();
+        let words = all_words(&["a", "b"], 4);
+        let expected = words.iter().map(|word| dfa.accepts(word)).collect::<Vec<_>>();
+        assert_eq!(dfa.accepts_all(words.iter().map(Vec::as_slice)), expected);
+        dfa.build_index();
+        assert_eq!(dfa.accepts_all(words.iter().map(Vec::as_slice)), expected);
+        assert!(dfa.accepts_all(std::iter::empty()).is_empty());
+    }
+
+    #[test]
+    fn into_transitions_test() {
+        let dfa = ends_with_ab();
+        let mut rebuilt = DFA::new();
+        rebuilt.add_initial_states(once("0")).unwrap();
+        rebuilt.add_finite_states(once("2")).unwrap();
+        for (from_state, input, to_state) in dfa.clone().into_transitions() {
+            rebuilt.add_transfer_input(&from_state.state_id, input, &to_state.state_id).unwrap();
+        }
+        assert_eq!(rebuilt, dfa);
+    }
+
+    #[test]
+    fn state_payload_test() {
+        // 识别关键字 if 与由 i、f 组成的标识符
+        let mut dfa = DFA::new();
+        dfa.add_initial_states(once("start")).unwrap();
+        dfa.add_finite_states(["i", "if", "id"].into_iter()).unwrap();
+        dfa.add_transfer_rule("start", "i", "i").unwrap();
+        dfa.add_transfer_rule("start", "f", "id").unwrap();
+        dfa.add_transfer_rule("i", "f", "if").unwrap();
+        dfa.add_transfer_rule("i", "i", "id").unwrap();
+        dfa.add_transfer_rule("if", "i", "id").unwrap();
+        dfa.add_transfer_rule("if", "f", "id").unwrap();
+        dfa.add_transfer_rule("id", "i", "id").unwrap();
+        dfa.add_transfer_rule("id", "f", "id").unwrap();
+        dfa.set_state_payload("i", "IDENT".to_string());
+        dfa.set_state_payload("id", "IDENT".to_string());
+        dfa.set_state_payload("if", "KEYWORD_IF".to_string());
+        let kind = |dfa: &DFA, input: &[&str]| dfa.run(input).and_then(|s| dfa.get_state_payload(&s.state_id).cloned());
+        assert_eq!(kind(&dfa, &["i", "f"]).as_deref(), Some("KEYWORD_IF"));
+        assert_eq!(kind(&dfa, &["i", "f", "f"]).as_deref(), Some("IDENT"));
+        assert_eq!(kind(&dfa, &[]), None);
+        // 重新编号后数据随状态一起迁移
+        dfa.normalize_ids();
+        assert_eq!(kind(&dfa, &["i", "f"]).as_deref(), Some("KEYWORD_IF"));
+        assert_eq!(kind(&dfa, &["f"]).as_deref(), Some("IDENT"));
+        assert!(dfa.get_state_payload("if").is_none());
+    }
+
+    #[test]
+    fn minimize_with_payloads_test() {
+        // 终态 1 与 2 等价，但分别对应不同的记号
+        let mut dfa = DFA::new();
+        dfa.add_initial_states(once("0")).unwrap();
+        dfa.add_finite_states(["1", "2"].into_iter()).unwrap();
+        dfa.add_transfer_rule("0", "a", "1").unwrap();
+        dfa.add_transfer_rule("0", "b", "2").unwrap();
+        dfa.set_state_payload("1", "A".to_string());
+        dfa.set_state_payload("2", "B".to_string());
+        assert_eq!(dfa.minimize().get_states_num(), 2);
+        let minimized = dfa.minimize_with_payloads();
+        assert_eq!(minimized.get_states_num(), 3);
+        let kind = |input: &[&str]| minimized.run(input).and_then(|s| minimized.get_state_payload(&s.state_id).cloned());
+        assert_eq!(kind(&["a"]).as_deref(), Some("A"));
+        assert_eq!(kind(&["b"]).as_deref(), Some("B"));
+        // 数据相同时照常合并
+        dfa.set_state_payload("2", "A".to_string());
+        assert_eq!(dfa.minimize_with_payloads().get_states_num(), 2);
+    }
+
+    #[test]
+    fn minimize_without_rules_test() {
+        // 没有任何弧的初态与终态也要参与划分
+        let mut dfa = DFA::new();
+        dfa.add_initial_states(once("0")).unwrap();
+        dfa.add_finite_states(once("0")).unwrap();
+        let minimized = dfa.minimize();
+        assert_eq!(minimized.initial_state, Some(State::new("0")));
+        assert!(minimized.accepts(&[]));
+        assert!(!minimized.accepts(&["a"]));
+        assert_eq!(dfa.minimization_partition(), vec![BTreeSet::from([State::new("0")])]);
+        assert!(dfa.minimize_with_payloads().accepts(&[]));
+    }
+
+    #[test]
+    fn minimization_partition_test() {
+        // 1 与 2 等价
+        let mut dfa = DFA::new();
+        dfa.add_initial_states(once("0")).unwrap();
+        dfa.add_finite_states(once("3")).unwrap();
+        dfa.add_transfer_rule("0", "a", "1").unwrap();
+        dfa.add_transfer_rule("0", "b", "2").unwrap();
+        dfa.add_transfer_rule("1", "a", "3").unwrap();
+        dfa.add_transfer_rule("2", "a", "3").unwrap();
+        assert_eq!(dfa.minimization_partition(), vec![
+            BTreeSet::from([State::new("0")]),
+            BTreeSet::from([State::new("1"), State::new("2")]),
+            BTreeSet::from([State::new("3")]),
+        ]);
+        assert_eq!(dfa.minimization_partition().len(), dfa.minimize().get_states_num());
+    }
+
+    #[test]
+    fn wildcard_test() {
+        let mut dfa = DFA::new();
+        dfa.add_initial_states(once("0")).unwrap();
+        dfa.add_finite_states(once("other")).unwrap();
+        dfa.add_transfer_rule("0", "a", "a").unwrap();
+        // 通配符与 a 重叠，转换到不同状态时不确定
+        assert!(matches!(dfa.add_transfer_input("0", Input::any(), "other"), Err(Error::UnsupportedOperation(_))));
+        dfa.add_transfer_input("0", Input::not(["a"]), "other").unwrap();
+        assert_eq!(dfa.run(&["a"]), Some(State::new("a")));
+        assert_eq!(dfa.run(&["b"]), Some(State::new("other")));
+        assert_eq!(dfa.run(&["while"]), Some(State::new("other")));
+        let complement = dfa.complement();
+        assert!(complement.accepts(&["a"]));
+        assert!(!complement.accepts(&["b"]));
+        assert!(complement.accepts(&["b", "while"]));
+        assert_eq!(dfa.count_accepted_by_length(1), vec![0, u64::MAX]);
+    }
+
+    #[test]
+    fn wildcard_algorithms_test() {
+        // d 接受不含 a 的串，all 接受所有串
+        let mut d = DFA::new();
+        d.add_initial_states(once("0")).unwrap();
+        d.add_finite_states(once("0")).unwrap();
+        d.add_transfer_rule("0", "a", "dead").unwrap();
+        d.add_transfer_input("0", Input::not(["a"]), "0").unwrap();
+        let mut all = DFA::new();
+        all.add_initial_states(once("0")).unwrap();
+        all.add_finite_states(once("0")).unwrap();
+        all.add_transfer_input("0", Input::any(), "0").unwrap();
+
+        assert!(d.is_subset_of(&all));
+        assert!(!all.is_subset_of(&d));
+        assert_eq!(all.difference(&d).shortest_accepted(), Some(vec!["a".to_string()]));
+        for dfa in [d.intersect(&all), all.intersect(&d)] {
+            assert!(dfa.accepts(&["b", "while"]));
+            assert!(!dfa.accepts(&["b", "a"]));
+        }
+        assert!(d.union(&all).accepts(&["a"]));
+        assert!(d.symmetric_difference(&all).accepts(&["b", "a"]));
+        assert!(!d.symmetric_difference(&all).accepts(&["b"]));
+
+        let mut pruned = d.clone();
+        pruned.remove_dead_states();
+        assert!(pruned.accepts(&["b"]));
+        assert!(!pruned.accepts(&["a"]));
+
+        let nfa = NFA::from(d.clone());
+        assert!(nfa.accepts(["b"].into_iter()).unwrap());
+        assert!(!nfa.accepts(["a"].into_iter()).unwrap());
+        let reversed = d.reverse();
+        assert!(reversed.accepts(["while", "b"].into_iter()).unwrap());
+        assert!(!reversed.accepts(["b", "a"].into_iter()).unwrap());
+        let minimal = d.minimize_brzozowski();
+        assert!(minimal.accepts(&["b"]));
+        assert!(!minimal.accepts(&["a"]));
+        assert_eq!(d.minimize().canonical_hash(), minimal.canonical_hash());
+
+        assert!(d.complement().accepts(&["b", "a"]));
+        assert!(!d.complement().accepts(&["b"]));
+        assert_ne!(d.canonical_hash(), all.canonical_hash());
+    }
+
+    #[test]
+    fn wildcard_range_classes_test() {
+        let single = |input: Input| {
+            let mut dfa = DFA::new();
+            dfa.add_initial_states(once("0")).unwrap();
+            dfa.add_finite_states(once("1")).unwrap();
+            dfa.add_transfer_input("0", input, "1").unwrap();
+            dfa
+        };
+        // 全字符区间与通配符之并只有两个输入类：这个区间，以及只匹配多字符输入字符的类
+        let union = single(Input::range('\0', char::MAX)).union(&single(Input::any()));
+        let (_, inputs, _) = union.to_dense_table();
+        assert_eq!(inputs, vec![format!("[\0-{}]", char::MAX), "<^>".to_string()]);
+        assert!(union.accepts(&["a"]) && union.accepts(&["while"]));
+
+        // 通配符减去 [a-z]，其余的单个字符由区间之间的空隙表示
+        let difference = single(Input::any()).difference(&single(Input::range('a', 'z')));
+        assert_eq!(difference.to_dense_table().1.len(), 4);
+        assert!(difference.accepts(&["A"]) && difference.accepts(&["while"]) && difference.accepts(&[char::MAX.to_string().as_str()]));
+        assert!(!difference.accepts(&["b"]));
+        assert_eq!(NFA::from_dsl(&difference.to_dsl()).unwrap().as_dfa().unwrap(), difference);
+        assert_eq!(NFA::from_json(&NFA::from(difference.clone()).to_json()).unwrap(), NFA::from(difference.clone()));
+        assert!(single(Input::range('a', 'z')).is_subset_of(&single(Input::any())));
+        assert!(!single(Input::any()).is_subset_of(&single(Input::range('\0', char::MAX))));
+    }
+
+    #[test]
+    fn product_ids_with_bar_test() {
+        // A 与 B 都只接受 "a"，它们的状态 id 中含有 `|`，积状态 "x|y|z" 不能被合并
+        let a = dfa!(
+            initial_state: "x";
+            finite_states: "x|y";
+            transfer_rules: "x" => "a" => "x|y").unwrap();
+        let b = dfa!(
+            initial_state: "y|z";
+            finite_states: "z";
+            transfer_rules: "y|z" => "a" => "z").unwrap();
+        let intersection = a.intersect(&b);
+        assert!(intersection.accepts(&["a"]));
+        assert!(!intersection.accepts(&[]));
+        assert!(!intersection.accepts(&["a", "a"]));
+        assert!(a.is_subset_of(&b) && b.is_subset_of(&a));
+        assert!(a.symmetric_difference(&b).is_empty());
+        assert!(intersection.has_rule("x|y\\|z", "a", "x\\|y|z"));
+        let c = dfa!(
+            initial_state: "x\\";
+            finite_states: "x\\";
+            transfer_rules: "x\\" => "a" => "x\\").unwrap();
+        assert_eq!(c.union(&c).initial_state, Some(State::new("x\\\\|x\\\\")));
+    }
+
+    #[test]
+    fn validate_test() {
+        let dfa = ends_with_ab();
+        assert!(dfa.validate().is_ok());
+
+        let mut no_initial = dfa.clone();
+        no_initial.initial_state = None;
+        assert!(matches!(no_initial.validate(), Err(Error::Uninitialized(_))));
+
+        let mut with_epsilon = dfa.clone();
+        with_epsilon.feasible_inputs.insert(Input::new("ɛ"));
+        assert!(matches!(with_epsilon.validate(), Err(Error::IllegalArgument(_))));
+
+        let mut nondeterministic = dfa.clone();
+        nondeterministic.adjacency_matrix.get_mut(&State::new("0")).unwrap()
+            .insert(State::new("2"), Edge::with_inputs([Input::new("a")]));
+        assert!(matches!(nondeterministic.validate(), Err(Error::UnsupportedOperation(_))));
+
+        let mut dangling = dfa;
+        dangling.adjacency_matrix.remove(&State::new("2"));
+        assert!(matches!(dangling.validate(), Err(Error::StateNotFound(s)) if s == "2"));
+    }
+
+    #[test]
+    fn accepts_checked_test() {
+        let dfa = only_ab();
+        assert!(dfa.accepts_checked(&["a", "b"]).unwrap());
+        assert!(!dfa.accepts_checked(&["b", "a"]).unwrap());
+        assert!(!dfa.accepts(&["a", "c"]));
+        assert!(matches!(dfa.accepts_checked(&["a", "c"]), Err(Error::IllegalArgument(_))));
+    }
+
+    #[test]
+    fn accepts_fast_test() {
+        let mut dfa = ends_with_ab();
+        dfa.build_index();
+        let long_input = ["a", "b", "b", "a"].repeat(10000);
+        let mut accepted = long_input.clone();
+        accepted.extend(["a", "b"]);
+        assert_eq!(dfa.accepts_fast(&long_input), dfa.accepts(&long_input));
+        assert!(dfa.accepts_fast(&accepted));
+        for word in all_words(&["a", "b"], 5) {
+            assert_eq!(dfa.accepts_fast(&word), dfa.accepts(&word), "{:?}", word);
+        }
+        // 修改后索引被清空
+        dfa.add_transfer_rule("2", "c", "2").unwrap();
+        assert!(dfa.transition_index.is_none());
+        assert!(dfa.accepts_fast(&["a", "b", "c"]));
+    }
+
+    #[test]
+    fn contains_test() {
+        let dfa = only_ab();
+        assert!(dfa.contains_state("0"));
+        assert!(dfa.contains_state("2"));
+        assert!(!dfa.contains_state("3"));
+        assert!(dfa.has_rule("0", "a", "1"));
+        assert!(!dfa.has_rule("0", "b", "1"));
+        assert!(!dfa.has_rule("0", "a", "2"));
+    }
+
+    #[test]
+    fn trace_test() {
+        let steps = only_ab().trace(&["a", "a", "b"]);
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].to_state, Some(State::new("1")));
+        assert_eq!(steps[1].from_state, State::new("1"));
+        assert_eq!(steps[1].input, "a");
+        assert_eq!(steps[1].to_state, None);
+        assert!(!steps[1].accepting);
+
+        let steps = only_ab().trace(&["a", "b"]);
+        assert_eq!(steps.len(), 2);
+        assert!(steps.last().unwrap().accepting);
+        assert!(DFA::new().trace(&["a"]).is_empty());
+    }
+
+    #[test]
+    fn symmetric_difference_test() {
+        let witness = ends_with_ab().symmetric_difference(&only_ab()).shortest_accepted().unwrap();
+        let witness = witness.iter().map(String::as_str).collect::<Vec<_>>();
+        assert_ne!(ends_with_ab().accepts(&witness), only_ab().accepts(&witness));
+        assert_eq!(witness, vec!["a", "a", "b"]);
+        for word in all_words(&["a", "b"], 4) {
+            assert_eq!(even_a().symmetric_difference(&ends_with_ab()).accepts(&word),
+                       even_a().accepts(&word) != ends_with_ab().accepts(&word), "{:?}", word);
+        }
+        assert!(ends_with_ab().symmetric_difference(&ends_with_ab().minimize()).is_empty());
+    }
+
+    #[test]
+    fn difference_test() {
+        for (dfa1, dfa2) in [(even_a(), ends_with_ab()), (ends_with_ab(), only_ab()), (only_ab(), even_a())] {
+            let difference = dfa1.difference(&dfa2);
+            for word in all_words(&["a", "b"], 5) {
+                assert_eq!(difference.accepts(&word), dfa1.accepts(&word) && !dfa2.accepts(&word), "{:?}", word);
+            }
+        }
+        // 只去掉重叠的 ab，保留其余以 ab 结尾的串
+        let difference = ends_with_ab().difference(&only_ab());
+        assert!(!difference.accepts(&["a", "b"]));
+        assert!(difference.accepts(&["b", "a", "b"]));
+        assert!(only_ab().difference(&ends_with_ab()).is_empty());
+    }
+
+    #[test]
+    fn is_subset_of_test() {
+        assert!(only_ab().is_subset_of(&ends_with_ab()));
+        assert!(!ends_with_ab().is_subset_of(&only_ab()));
+        assert!(ends_with_ab().is_subset_of(&ends_with_ab()));
+        assert!(ends_with_ab().is_subset_of(&ends_with_ab().minimize()));
+        assert!(DFA::new().is_subset_of(&only_ab()));
+        // self 含有 other 字母表以外的输入字符
+        let mut dfa = only_ab();
+        dfa.add_transfer_rule("0", "c", "2").unwrap();
+        assert!(!dfa.is_subset_of(&ends_with_ab()));
+    }
+
+    #[test]
+    fn is_empty_test() {
+        assert!(!ends_with_ab().is_empty());
+        assert!(!even_a().is_empty());
+        assert!(even_a().intersect(&only_ab()).is_empty());
+        assert!(DFA::new().is_empty());
+    }
+
+    #[test]
+    fn is_finite_test() {
+        assert!(!ends_with_ab().is_finite());
+        assert!(!even_a().is_finite());
+        assert!(only_ab().is_finite());
+        // 陷阱状态上的环不影响有穷性
+        assert!(only_ab().complement().complement().is_finite());
+        assert!(DFA::new().is_finite());
+    }
+
+    #[test]
+    fn to_regex_test() {
+        let mut dfa = DFA::new();
+        dfa.add_initial_states(once("0")).unwrap();
+        dfa.add_finite_states(once("1")).unwrap();
+        dfa.add_transfer_rule("0", "a", "1").unwrap();
+        dfa.add_transfer_rule("1", "b", "1").unwrap();
+        assert_eq!(dfa.to_regex(), "ab*");
+
+        for dfa in [dfa, ends_with_ab(), even_a(), only_ab(), only_ab().complement()] {
+            let mut nfa = crate::regex::from_regex(&dfa.to_regex()).unwrap();
+            nfa.calc_epsilon_closure_matrix();
+            for word in all_words(&["a", "b"], 5) {
+                assert_eq!(nfa.accepts(word.iter().copied()).unwrap(), dfa.accepts(&word), "{:?}", word);
+            }
+        }
+        assert_eq!(even_a().intersect(&only_ab()).to_regex(), "∅");
+
+        // 字面的 . 与 [ 被转义，不会与通配符、字符区间混淆，结果仍能被 from_regex 解析
+        let mut dfa = DFA::new();
+        dfa.add_initial_states(once("0")).unwrap();
+        dfa.add_finite_states(once("1")).unwrap();
+        dfa.add_transfer_rule("0", ".", "1").unwrap();
+        dfa.add_transfer_rule("1", "[", "1").unwrap();
+        assert_eq!(dfa.to_regex(), "\\.\\[*");
+        let mut nfa = crate::regex::from_regex(&dfa.to_regex()).unwrap();
+        nfa.calc_epsilon_closure_matrix();
+        for word in all_words(&[".", "[", "a"], 3) {
+            assert_eq!(nfa.accepts(word.iter().copied()).unwrap(), dfa.accepts(&word), "{:?}", word);
+        }
+        let mut wildcard = DFA::new();
+        wildcard.add_initial_states(once("0")).unwrap();
+        wildcard.add_finite_states(once("1")).unwrap();
+        wildcard.add_transfer_input("0", Input::any(), "1").unwrap();
+        wildcard.add_transfer_input("1", Input::range('a', 'z'), "1").unwrap();
+        assert_eq!(wildcard.to_regex(), ".[a-z]*");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_test() {
+        let dfa = ends_with_ab();
+        let json = serde_json::to_string(&dfa).unwrap();
+        let deserialized: DFA = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.initial_state, dfa.initial_state);
+        assert_eq!(deserialized.finite_states, dfa.finite_states);
+        assert_eq!(deserialized.feasible_inputs, dfa.feasible_inputs);
+        assert_eq!(format!("{:?}", deserialized.adjacency_matrix), format!("{:?}", dfa.adjacency_matrix));
+    }
+
+    #[test]
+    fn reverse_test() {
+        for dfa in [ends_with_ab(), even_a(), only_ab()] {
+            let mut reversed = dfa.reverse();
+            reversed.calc_epsilon_closure_matrix();
+            assert_eq!(reversed.feasible_inputs, dfa.feasible_inputs);
+            for word in all_words(&["a", "b"], 5) {
+                assert_eq!(reversed.accepts(word.iter().rev().copied()).unwrap(), dfa.accepts(&word), "{:?}", word);
+            }
+        }
+        let mut reversed = only_ab().reverse();
+        reversed.calc_epsilon_closure_matrix();
+        assert!(reversed.accepts(["b", "a"].into_iter()).unwrap());
+        assert!(!reversed.accepts(["a", "b"].into_iter()).unwrap());
+    }
+
+    #[test]
+    fn minimize_brzozowski_test() {
+        // 接受所有以 ab 结尾的 {a, b} 串，其中 3 与 1 等价
+        let mut redundant = DFA::new();
+        redundant.add_initial_states(once("0")).unwrap();
+        redundant.add_finite_states(once("2")).unwrap();
+        redundant.add_transfer_rule("0", "a", "1").unwrap();
+        redundant.add_transfer_rule("0", "b", "0").unwrap();
+        redundant.add_transfer_rule("1", "a", "3").unwrap();
+        redundant.add_transfer_rule("1", "b", "2").unwrap();
+        redundant.add_transfer_rule("2", "a", "3").unwrap();
+        redundant.add_transfer_rule("2", "b", "0").unwrap();
+        redundant.add_transfer_rule("3", "a", "1").unwrap();
+        redundant.add_transfer_rule("3", "b", "2").unwrap();
+        for dfa in [ends_with_ab(), redundant, even_a()] {
+            let minimized = dfa.minimize_brzozowski();
+            assert_eq!(minimized.get_states_num(), dfa.minimize().get_states_num());
+            for word in all_words(&["a", "b"], 5) {
+                assert_eq!(minimized.accepts(&word), dfa.accepts(&word), "{:?}", word);
+            }
+        }
+        assert_eq!(ends_with_ab().minimize_brzozowski().get_states_num(), 3);
+        let empty = only_ab().intersect(&even_a()).minimize_brzozowski();
+        assert_eq!(empty.get_states_num(), 0);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn builder_test() {
+        let dfa = DfaBuilder::new()
+            .initial("0")
+            .finite("1")
+            .rule("0", "a", "1")
+            .rule("1", "a", "1")
+            .build()
+            .unwrap();
+        assert!(dfa.accepts(&["a", "a"]));
+        assert!(!dfa.accepts(&[]));
+        assert!(matches!(DfaBuilder::new().rule("0", "a", "1").build(),
+                         Err(Error::IllegalArgument(_))));
+        assert!(matches!(DfaBuilder::new().initial("0").initial("1").build(),
+                         Err(Error::IllegalArgument(_))));
+        assert!(matches!(DfaBuilder::new().initial("0").rule("0", "a", "1").rule("0", "a", "2").build(),
+                         Err(Error::UnsupportedOperation(_))));
+        assert!(matches!(DfaBuilder::new().initial("0").rule("0", "ɛ", "1").build(),
+                         Err(Error::IllegalArgument(_))));
+    }
+
+    #[test]
+    fn statistics_test() {
+        assert_eq!(only_ab().num_edges(), 2);
+        assert_eq!(ends_with_ab().num_edges(), 6);
+        assert_eq!(ends_with_ab().num_epsilon_edges(), 0);
+        assert_eq!(ends_with_ab().alphabet_size(), 2);
+    }
+
+    #[test]
+    fn normalize_ids_test() {
+        let mut dfa = ends_with_ab().intersect(&even_a());
+        assert!(dfa.get_all_states_iter().any(|s| s.state_id.contains('|')));
+        dfa.normalize_ids();
+        let ids = dfa.get_all_states_iter().map(|s| s.state_id.clone()).collect::<BTreeSet<_>>();
+        assert_eq!(ids, (0..dfa.get_states_num()).map(|i| i.to_string()).collect());
+        assert_eq!(dfa.initial_state, Some(State::new("0")));
+        for word in all_words(&["a", "b"], 5) {
+            assert_eq!(dfa.accepts(&word), ends_with_ab().accepts(&word) && even_a().accepts(&word), "{:?}", word);
+        }
+    }
+
+    #[test]
+    fn to_table_test() {
+        let table = only_ab().to_table();
+        assert_eq!(table, [
+            "      | a | b",
+            " -> 0 | 1 |",
+            "    1 |   | 2",
+            "  * 2 |   |",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn remove_transfer_rule_test() {
+        let mut dfa = only_ab();
+        dfa.remove_transfer_rule("1", "b", "2").unwrap();
+        assert!(dfa.adjacency_matrix[&State::new("1")].is_empty());
+        assert_eq!(dfa.feasible_inputs, BTreeSet::from([Input::new("a")]));
+        assert!(!dfa.accepts(&["a", "b"]));
+        assert!(matches!(dfa.remove_transfer_rule("1", "b", "2"), Err(Error::IllegalArgument(_))));
+        // 删除后可以添加一条原本会造成不确定的规则
+        dfa.add_transfer_rule("0", "b", "2").unwrap();
+        dfa.remove_transfer_rule("0", "a", "1").unwrap();
+        dfa.add_transfer_rule("0", "a", "2").unwrap();
+        assert!(dfa.accepts(&["a"]));
+    }
+
+    #[test]
+    fn display_test() {
+        let dfa = only_ab();
+        let display = format!("{}", dfa);
+        assert!(display.contains("initial_state: Some(0)"));
+        assert!(display.contains("0 => {a} => 1"));
+        assert_eq!(display, format!("{:?}", dfa));
+    }
+
+    #[test]
+    fn eq_test() {
+        let mut dfa = only_ab();
+        assert_eq!(dfa, only_ab());
+        assert_ne!(dfa, ends_with_ab());
+        dfa.add_transfer_rule("2", "a", "0").unwrap();
+        assert_ne!(dfa, only_ab());
+        // 结构相等而非语言相等：两次求补后多出一个陷阱状态
+        assert_ne!(only_ab().complement().complement(), only_ab());
+    }
+
+    #[test]
+    fn clone_test() {
+        let mut dfa = only_ab();
+        dfa.add_transfer_rule("3", "a", "2").unwrap();
+        let snapshot = dfa.clone();
+        assert_eq!(dfa, snapshot);
+        dfa.remove_unreachable();
+        assert_ne!(dfa, snapshot);
+        assert_eq!(snapshot.get_states_num(), 4);
+    }
+
+    #[test]
+    fn to_dot_test() {
+        let dot = ends_with_ab().to_dot();
+        assert!(dot.contains("\"2\" [shape=doublecircle];"));
+        assert!(dot.contains("__start0 -> \"0\";"));
+        assert!(!dot.contains("__start1"));
+        assert!(dot.contains("\"0\" -> \"1\" [label=\"a\"];"));
+        assert!(dot.contains("\"2\" -> \"0\" [label=\"b\"];"));
+    }
 }
\ No newline at end of file
@@ -1,97 +1,146 @@
-use std::{rc::Rc, cell::RefCell, collections::BTreeMap, ops::Deref, borrow::Borrow};
+use std::{cell::Cell, borrow::Borrow};
 
 use bimap::BiBTreeMap;
 
-/// 并查集，朴素实现，没有做任何优化
+/// 并查集，使用按秩合并与路径压缩
+///
+/// ```
+/// use automata::disjoint_set::DisjointSet;
+///
+/// let mut disjoint_set = DisjointSet::new();
+/// disjoint_set.add_element("x");
+/// disjoint_set.add_element("y");
+/// disjoint_set.add_element_to("z", "x");
+/// assert!(disjoint_set.same_class("x", "z"));
+/// assert!(!disjoint_set.same_class("x", "y"));
+/// disjoint_set.join("x", "y");
+/// assert!(disjoint_set.same_class("z", "y"));
+/// assert_eq!(disjoint_set.get_father("z"), Some(&"y"));
+/// ```
 pub struct DisjointSet<T> {
     /// 映射表，将每个 element 映射一个 usize id
     elements: BiBTreeMap<T, usize>,
     /// 直接父亲数组，第 i 个值为 id 为 i 的元素的直接父亲
-    fathers: Vec<usize>,
+    /// 使用 `Cell` 以便在只读查询时也能进行路径压缩
+    fathers: Vec<Cell<usize>>,
+    /// 秩数组，第 i 个值为以 id 为 i 的元素为根的树的高度上界，只对根有意义
+    /// 直接挂在根下的叶子不计入秩
+    ranks: Vec<usize>,
 }
 
 impl<T> DisjointSet<T>
     where T: Ord {
     /// 创建一个新的，空的并查集
-    fn new() -> Self {
+    pub fn new() -> Self {
         DisjointSet {
             elements: BiBTreeMap::new(),
             fathers: Vec::new(),
+            ranks: Vec::new(),
         }
     }
 
     /// 添加一个新元素，单独成一类
-    fn add_element(&mut self, element: impl Into<T>) {
+    pub fn add_element(&mut self, element: impl Into<T>) {
         let id = self.elements.len();
         self.elements.insert(element.into(), id);
-        self.fathers.push(id);
+        self.fathers.push(Cell::new(id));
+        self.ranks.push(0);
     }
 
     /// 添加一个新元素，与 `class_element` 同一类
-    fn add_element_to(&mut self, element: impl Into<T>, class_element: impl Borrow<T>) {
+    pub fn add_element_to(&mut self, element: impl Into<T>, class_element: impl Borrow<T>) {
+        let class_id = *self.get_id(class_element).unwrap();
+        self.add_element_to_id(element, class_id);
+    }
+
+    /// 添加一个新元素，与 id 为 class_id 的元素同一类，新元素直接挂到该类的根下
+    fn add_element_to_id(&mut self, element: impl Into<T>, class_id: usize) {
+        let class_root_id = self.get_father_by_id(class_id).unwrap();
         let id = self.elements.len();
         self.elements.insert(element.into(), id);
-        let class_element_id = self.get_id(class_element).unwrap();
-        self.fathers.push(*class_element_id);
+        self.fathers.push(Cell::new(class_root_id));
+        self.ranks.push(0);
     }
 
     /// 添加 elements 中所有元素，单独成一类
-    fn add_elements<I>(&mut self, elements: I)
+    pub fn add_elements<I>(&mut self, elements: I)
         where I: IntoIterator<Item = T> {
-            let mut iter = elements.into_iter();
-            if let Some(first_element) = iter.by_ref().next() { // 先取一个元素
-                let class_id = self.elements.len(); // 为其开辟一个新类
-                self.elements.insert(first_element, class_id);
-                self.fathers.push(class_id);
-                iter.for_each(|element| { // 迭代器中其他元素和第一个元素属于同一类
-                    let id = self.elements.len();
-                    self.elements.insert(element, id);
-                    self.fathers.push(class_id);
-                })
-            }
+        let mut iter = elements.into_iter();
+        if let Some(first_element) = iter.next() { // 先取一个元素，为其开辟一个新类
+            let class_id = self.elements.len();
+            self.add_element(first_element);
+            // 迭代器中其他元素和第一个元素属于同一类
+            iter.for_each(|element| self.add_element_to_id(element, class_id));
+        }
     }
 
     /// 返回指定 id 对应的元素
-    fn get_element(&self, id: &usize) -> Option<&T> {
+    pub fn get_element(&self, id: &usize) -> Option<&T> {
         self.elements.get_by_right(id)
     }
 
     /// 返回指定元素对应的 id
-    fn get_id(&self, element: impl Borrow<T>) -> Option<&usize> {
+    pub fn get_id(&self, element: impl Borrow<T>) -> Option<&usize> {
         self.elements.get_by_left(element.borrow())
     }
 
     /// 返回指定元素的父亲
-    fn get_father(&self, element: impl Borrow<T>) -> Option<&T> {
+    pub fn get_father(&self, element: impl Borrow<T>) -> Option<&T> {
         self.get_id(element)
             .and_then(|element_id| self.get_father_by_id(*element_id))
             .and_then(|father_id| self.get_element(&father_id))
     }
 
     /// 返回指定 id 所表示的元素的父亲
+    /// 查询时会进行路径压缩，使路径上的所有元素直接指向父亲
     fn get_father_by_id(&self, id: usize) -> Option<usize> {
-        self.fathers.get(id)
-            .and_then(|straight_father_id| {
-                if id == *straight_father_id {
-                    Some(id)
-                } else {
-                    self.get_father_by_id(*straight_father_id)
-                }
-            })
+        // 第一遍，找到根
+        let mut root_id = id;
+        loop {
+            let straight_father_id = self.fathers.get(root_id)?.get();
+            if straight_father_id == root_id {
+                break;
+            }
+            root_id = straight_father_id;
+        }
+        // 第二遍，将路径上的所有元素直接挂到根下
+        let mut current_id = id;
+        while current_id != root_id {
+            current_id = self.fathers[current_id].replace(root_id);
+        }
+        Some(root_id)
     }
 
     /// 合并两个 id 所表示的元素 (使之拥有相同的父亲)
-    fn join_by_id(&mut self, id1: usize, id2: usize) {
-        if let (Some(f1), Some(f2)) = 
+    /// 秩较小的树挂到秩较大的树下；秩相同时，id1 所在的树挂到 id2 所在的树下
+    pub fn join_by_id(&mut self, id1: usize, id2: usize) {
+        if let (Some(f1), Some(f2)) =
                (self.get_father_by_id(id1), self.get_father_by_id(id2)) {
-            if f1 != f2 { unsafe {
-                *(self.fathers.get_unchecked_mut(f1)) = f2;
-            }}
+            if f1 == f2 {
+                return;
+            }
+            if self.ranks[f1] > self.ranks[f2] {
+                self.fathers[f2].set(f1);
+            } else {
+                self.fathers[f1].set(f2);
+                if self.ranks[f1] == self.ranks[f2] {
+                    self.ranks[f2] += 1;
+                }
+            }
+        }
+    }
+
+    /// 判断两个元素是否属于同一类 (拥有相同的父亲)
+    /// 若任一元素不在并查集中，返回 `false`
+    pub fn same_class(&self, element1: impl Borrow<T>, element2: impl Borrow<T>) -> bool {
+        match (self.get_father(element1), self.get_father(element2)) {
+            (Some(f1), Some(f2)) => f1 == f2,
+            _ => false,
         }
     }
 
     /// 合并两个元素 (使之拥有相同的父亲)
-    fn join(&mut self, element1: impl Borrow<T>, element2: impl Borrow<T>) {
+    pub fn join(&mut self, element1: impl Borrow<T>, element2: impl Borrow<T>) {
         if let (Some(id1), Some(id2)) =
                (self.get_id(element1), self.get_id(element2)) {
             self.join_by_id(*id1, *id2);
@@ -99,10 +148,15 @@ impl<T> DisjointSet<T>
     }
 }
 
+impl<T> Default for DisjointSet<T>
+    where T: Ord {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::borrow::Borrow;
-
     use super::DisjointSet;
 
     #[test]
@@ -121,4 +175,43 @@ mod tests {
     fn test2() {
         
     }
+
+    #[test]
+    fn add_elements_test() {
+        let mut disjoint_set = DisjointSet::new();
+        disjoint_set.add_element("x");
+        disjoint_set.add_elements(["a", "b", "c"]);
+        assert!(disjoint_set.same_class("a", "b"));
+        assert!(disjoint_set.same_class("b", "c"));
+        assert!(disjoint_set.same_class("a", "c"));
+        assert!(!disjoint_set.same_class("a", "x"));
+        assert_eq!(disjoint_set.get_father("c"), Some(&"a"));
+        disjoint_set.add_elements([]);
+        assert_eq!(disjoint_set.get_id("c"), Some(&3));
+    }
+
+    /// 不做路径压缩地计算 id 所表示的元素到根的距离
+    fn depth<T: Ord>(disjoint_set: &DisjointSet<T>, id: usize) -> usize {
+        let mut depth = 0;
+        let mut current_id = id;
+        while disjoint_set.fathers[current_id].get() != current_id {
+            current_id = disjoint_set.fathers[current_id].get();
+            depth += 1;
+        }
+        depth
+    }
+
+    #[test]
+    fn long_chain_test() {
+        let n = 1 << 12;
+        let mut disjoint_set = DisjointSet::new();
+        (0..n).for_each(|i| disjoint_set.add_element(i));
+        // 依次合并相邻元素，朴素实现会得到一条长为 n 的链
+        (1..n).for_each(|i| disjoint_set.join(i - 1, i));
+        // 按秩合并保证树高不超过 log2(n)
+        assert!((0..n).all(|i| depth(&disjoint_set, i) <= 12));
+        // 路径压缩后，每个元素都直接挂在根下
+        (0..n).for_each(|i| assert_eq!(disjoint_set.get_father(i), disjoint_set.get_father(0)));
+        assert!((0..n).all(|i| depth(&disjoint_set, i) <= 1));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,26 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Debug, Formatter};
+use crate::edge::Edge;
+use crate::state::State;
+
+/// 以多行的可读格式输出一个有限自动机，NFA 与 DFA 的 `Debug` 与 `Display` 共用此格式
+/// initial_states_field 为初态字段的名称，initial_states 为其内容 (NFA 为状态集，DFA 为 `Option<State>`)
+pub(crate) fn fmt_automaton(f: &mut Formatter<'_>,
+                            initial_states_field: &str,
+                            initial_states: &dyn Debug,
+                            finite_states: &BTreeSet<State>,
+                            adjacency_matrix: &BTreeMap<State, BTreeMap<State, Edge>>) -> std::fmt::Result {
+    let mut ret = String::new();
+    ret.push_str(&format!("FiniteAutomaton {{\n    {}: ", initial_states_field));
+    ret.push_str(&format!("{:?}", initial_states));
+    ret.push_str("\n    finite_states: ");
+    ret.push_str(&format!("{:?}", finite_states));
+    ret.push_str("\n    transfer_rules: ");
+    for (from_state, to_map) in adjacency_matrix {
+        for (to_state, edge) in to_map {
+            ret.push_str(&format!("\n        {:?} => {:?} => {:?}", from_state, edge, to_state));
+        }
+    }
+    ret.push_str("\n}");
+    write!(f, "{}", ret)
+}
@@ -0,0 +1,32 @@
+use std::collections::{BTreeMap, BTreeSet};
+use crate::edge::Edge;
+use crate::state::State;
+
+/// 将一个有限自动机渲染为 Graphviz DOT 格式
+/// 终态使用双圈表示，每个初态都有一条来自不可见结点的箭头，ɛ 弧标记为 `ε`
+pub(crate) fn to_dot<'a, I>(initial_states: I,
+                            finite_states: &BTreeSet<State>,
+                            adjacency_matrix: &BTreeMap<State, BTreeMap<State, Edge>>) -> String
+    where I: Iterator<Item = &'a State> {
+    let mut ret = String::new();
+    ret.push_str("digraph {\n    rankdir=LR;\n");
+    for state in adjacency_matrix.keys().chain(finite_states.iter()).collect::<BTreeSet<_>>() {
+        let shape = if finite_states.contains(state) { "doublecircle" } else { "circle" };
+        ret.push_str(&format!("    {:?} [shape={}];\n", state.state_id, shape));
+    }
+    for (i, initial_state) in initial_states.enumerate() {
+        ret.push_str(&format!("    __start{} [shape=point, style=invis];\n", i));
+        ret.push_str(&format!("    __start{} -> {:?};\n", i, initial_state.state_id));
+    }
+    for (from_state, to_map) in adjacency_matrix {
+        for (to_state, edge) in to_map {
+            let label = edge.input_set.iter()
+                .map(|input| if input.is_epsilon() { "ε".to_string() } else { input.to_string() })
+                .collect::<Vec<_>>()
+                .join(",");
+            ret.push_str(&format!("    {:?} -> {:?} [label={:?}];\n", from_state.state_id, to_state.state_id, label));
+        }
+    }
+    ret.push('}');
+    ret
+}
@@ -0,0 +1,195 @@
+use std::collections::{BTreeMap, BTreeSet};
+use crate::edge::Edge;
+use crate::input::Input;
+use crate::result::{Error, IResult};
+use crate::state::State;
+
+/// 解析后的文本描述：初态、终态与转换规则 (from_state, input, to_state)，状态 id 均已去除转义
+pub(crate) struct Document {
+    pub initial_states: Vec<String>,
+    pub finite_states: Vec<String>,
+    pub rules: Vec<(String, Input, String)>,
+}
+
+/// 解析逐行的文本描述，格式见 [`crate::nfa::NFA::from_dsl`]
+/// 格式错误时抛出 [`Error::IllegalLine`]，行号从 1 开始
+pub(crate) fn parse(src: &str) -> IResult<Document> {
+    let mut document = Document {
+        initial_states: Vec::new(),
+        finite_states: Vec::new(),
+        rules: Vec::new(),
+    };
+    for (i, line) in src.lines().enumerate() {
+        let line = line.trim();
+        let unescape_state = |s: &str| unescape(s).ok_or(Error::IllegalLine(i + 1, "Malformed escape sequence in a state id."));
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(states) = line.strip_prefix("initial:") {
+            if states.trim().is_empty() {
+                return Err(Error::IllegalLine(i + 1, "Expected at least one state after `initial:`."));
+            }
+            for s in states.split_whitespace() {
+                document.initial_states.push(unescape_state(s)?);
+            }
+        } else if let Some(states) = line.strip_prefix("final:") {
+            for s in states.split_whitespace() {
+                document.finite_states.push(unescape_state(s)?);
+            }
+        } else {
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+            let (from_state_id, arrow, to_state_id) = match tokens.as_slice() {
+                [from_state_id, arrow, to_state_id] => (*from_state_id, *arrow, *to_state_id),
+                _ => return Err(Error::IllegalLine(i + 1, "Expected `initial:`, `final:` or a rule like `X -a-> Y`.")),
+            };
+            let input = arrow.strip_prefix('-')
+                .and_then(|s| s.strip_suffix("->"))
+                .filter(|s| !s.is_empty())
+                .ok_or(Error::IllegalLine(i + 1, "Expected an arrow like `-a->` between two states."))?;
+            let input = unescape(input)
+                .map(|s| parse_input(&s))
+                .ok_or(Error::IllegalLine(i + 1, "Malformed escape sequence in an input."))?;
+            document.rules.push((unescape_state(from_state_id)?, input, unescape_state(to_state_id)?));
+        }
+    }
+    Ok(document)
+}
+
+/// 将一个有限自动机写为 [`parse`] 所接受的文本描述
+/// 依次为 `initial:` 行、`final:` 行 (终态集为空时省略) 与按起点、终点、输入排序的转换规则，每个输入单独一行
+/// 状态 id 与输入都经过 [`escape`] 转义，因此任意的状态 id 与输入都能被原样解析回来
+pub(crate) fn to_dsl(initial_states: &BTreeSet<State>,
+                     finite_states: &BTreeSet<State>,
+                     adjacency_matrix: &BTreeMap<State, BTreeMap<State, Edge>>) -> String {
+    let states_dsl = |states: &BTreeSet<State>| states.iter()
+        .map(|s| escape(&s.state_id))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut ret = String::new();
+    if !initial_states.is_empty() {
+        ret.push_str(&format!("initial: {}\n", states_dsl(initial_states)));
+    }
+    if !finite_states.is_empty() {
+        ret.push_str(&format!("final: {}\n", states_dsl(finite_states)));
+    }
+    for (from_state, to_map) in adjacency_matrix {
+        for (to_state, edge) in to_map {
+            for input in &edge.input_set {
+                ret.push_str(&format!("{} -{}-> {}\n",
+                                      escape(&from_state.state_id), escape(&write_input(input)), escape(&to_state.state_id)));
+            }
+        }
+    }
+    ret
+}
+
+/// 将弧上的输入写为 [`parse_input`] 能还原的形式
+fn write_input(input: &Input) -> String {
+    match input {
+        // 会被解析为其他输入的输入字符需要以 `\` 转义
+        Input::Char(s) if parse_input(s) != *input => format!("\\{}", s),
+        Input::Not { excluded, multi_char_only: false } if excluded.iter().any(|s| s.chars().count() > 1) => {
+            let mut parts = excluded.iter().map(String::as_str).collect::<Vec<_>>().join(",");
+            if excluded.len() == 1 {
+                parts.push(',');
+            }
+            format!("[^{}]", parts)
+        },
+        _ => input.to_string(),
+    }
+}
+
+/// 解析 (已去除转义的) 弧上的输入：`.` 为通配符，`[a-z]` 为字符区间，`[^abc]` 或 `[^if,else]` 为取反字符类，
+/// `<^if,else>` 为只匹配多字符输入字符的取反字符类，
+/// 以 `\` 开头时其余部分为字面的输入字符，其他为普通的输入字符
+fn parse_input(s: &str) -> Input {
+    if let Some(literal) = s.strip_prefix('\\') {
+        return Input::new(literal);
+    }
+    if s == "." {
+        return Input::any();
+    }
+    if let Some(excluded) = s.strip_prefix("[^").and_then(|s| s.strip_suffix(']')) {
+        // 以 `,` 分隔 (允许末尾多一个 `,`) 的写法中至少有一个输入字符由多个 `char` 组成，否则视为紧挨着写出的单个字符
+        if excluded.contains(',') {
+            let parts = excluded.strip_suffix(',').unwrap_or(excluded).split(',').collect::<Vec<_>>();
+            if parts.iter().all(|part| !part.is_empty()) && parts.iter().any(|part| part.chars().count() > 1) {
+                return Input::not(parts);
+            }
+        }
+        return Input::not(excluded.chars().map(String::from));
+    }
+    if let Some(excluded) = s.strip_prefix("<^").and_then(|s| s.strip_suffix('>')) {
+        return Input::Not {
+            excluded: excluded.split(',').filter(|part| !part.is_empty()).map(String::from).collect(),
+            multi_char_only: true,
+        };
+    }
+    match s.chars().collect::<Vec<_>>().as_slice() {
+        ['[', start, '-', end, ']'] => Input::range(*start, *end),
+        _ => Input::new(s),
+    }
+}
+
+/// 转义后紧跟在 `\` 之后、有特殊含义的字符
+const ESCAPES: [char; 9] = ['\\', 's', 't', 'n', 'r', 'e', 'u', '#', ':'];
+
+/// 转义状态 id 或弧上的输入，使其中不含空白，不为空串，不以 `#` 开头，也不以 `initial:` 或 `final:` 开头
+/// 空白写作 `\s`、`\t`、`\n`、`\r` 或 `\u{3000}`，空串写作 `\e`，开头的 `#` 写作 `\#`，
+/// 以 `initial:` 或 `final:` 开头时其中的 `:` 写作 `\:`；后面紧跟这些转义的 `\` 写作 `\\`，其余的 `\` 保持不变
+fn escape(s: &str) -> String {
+    if s.is_empty() {
+        return "\\e".to_string();
+    }
+    let is_header = s.starts_with("initial:") || s.starts_with("final:");
+    let mut ret = String::new();
+    if s.starts_with('#') {
+        ret.push('\\');
+    }
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' => ret.push_str("\\s"),
+            '\t' => ret.push_str("\\t"),
+            '\n' => ret.push_str("\\n"),
+            '\r' => ret.push_str("\\r"),
+            c if c.is_whitespace() => ret.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            ':' if is_header => ret.push_str("\\:"),
+            '\\' if chars.peek().is_some_and(|c| ESCAPES.contains(c) || c.is_whitespace()) => ret.push_str("\\\\"),
+            c => ret.push(c),
+        }
+    }
+    ret
+}
+
+/// 还原 [`escape`] 转义的状态 id 或输入，`\` 后不是转义字符时保持不变；`\u{..}` 不合法时返回 `None`
+fn unescape(s: &str) -> Option<String> {
+    let mut ret = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            ret.push(c);
+            continue;
+        }
+        match chars.clone().next() {
+            Some(escaped) if ESCAPES.contains(&escaped) => {
+                chars.next();
+                match escaped {
+                    's' => ret.push(' '),
+                    't' => ret.push('\t'),
+                    'n' => ret.push('\n'),
+                    'r' => ret.push('\r'),
+                    'e' => {},
+                    'u' => {
+                        let code = chars.as_str().strip_prefix('{')?.split_once('}')?.0;
+                        ret.push(u32::from_str_radix(code, 16).ok().and_then(char::from_u32)?);
+                        chars = chars.as_str()[code.len() + 2..].chars();
+                    },
+                    escaped => ret.push(escaped),
+                }
+            },
+            _ => ret.push('\\'),
+        }
+    }
+    Some(ret)
+}
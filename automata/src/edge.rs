@@ -1,7 +1,13 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{Debug, Formatter};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use crate::input::Input;
+use crate::result::{Error, IResult};
+use crate::state::State;
 
+#[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Edge {
     pub input_set: BTreeSet<Input>
 }
@@ -15,14 +21,182 @@ impl Edge {
         }
     }
 
-    /// 判断是否包含指定输入字符
+    /// 判断是否有输入能匹配指定输入字符
     pub fn contains_input(&self, input_str: &str) -> bool {
-        self.input_set.contains(&Input::new(input_str))
+        self.input_set.iter().any(|input| input.matches_str(input_str))
     }
 
-    /// 增加一个输入字符
-    pub fn add_input(&mut self, input_str: &str) {
-        self.input_set.insert(Input::new(input_str));
+    /// 与 [`Edge::contains_input`] 相同，但忽略大小写
+    pub fn contains_input_ignore_case(&self, input_str: &str) -> bool {
+        self.input_set.iter().any(|input| input.matches_str_ignore_case(input_str))
+    }
+
+    /// 判断是否有输入能覆盖指定输入，即能被 input 匹配的输入字符都能通过这条弧
+    pub fn covers(&self, input: &Input) -> bool {
+        self.input_set.iter().any(|i| i.covers(input))
+    }
+
+    /// 增加一个输入
+    pub fn add_input(&mut self, input: Input) {
+        self.input_set.insert(input);
+    }
+}
+
+/// 从邻接矩阵中删除一条转换规则，弧上不再有任何输入时删除整条弧
+/// 若已没有任何弧使用 input，则同时将其从 feasible_inputs 中删除
+/// 起点或终点不存在时抛出 [`Error::StateNotFound`]，两者都存在但规则不存在时抛出 [`Error::IllegalArgument`]
+pub(crate) fn remove_transfer_rule(adjacency_matrix: &mut BTreeMap<State, BTreeMap<State, Edge>>,
+                                   feasible_inputs: &mut BTreeSet<Input>,
+                                   from_state_id: &str, input: &Input, to_state_id: &str) -> IResult<()> {
+    let to_state = State::new(to_state_id);
+    if !adjacency_matrix.contains_key(&State::new(from_state_id)) {
+        return Err(Error::StateNotFound(from_state_id.to_string()));
+    }
+    if !adjacency_matrix.contains_key(&to_state) {
+        return Err(Error::StateNotFound(to_state_id.to_string()));
+    }
+    // 上面已检查起点存在，因此 unwrap 不会失败
+    let to_map = adjacency_matrix.get_mut(&State::new(from_state_id)).unwrap();
+    let edge = to_map.get_mut(&to_state)
+        .filter(|edge| edge.input_set.contains(input))
+        .ok_or(Error::IllegalArgument("The transfer rule does not exist."))?;
+    edge.input_set.remove(input);
+    if edge.input_set.is_empty() {
+        to_map.remove(&to_state);
+    }
+    if !adjacency_matrix.values().flat_map(|to_map| to_map.values()).any(|edge| edge.input_set.contains(input)) {
+        feasible_inputs.remove(input);
+    }
+    Ok(())
+}
+
+/// 将邻接矩阵中所有弧上的输入 old 改为 new，并相应地更新 feasible_inputs；弧上已有 new 时两者合并为一个输入
+/// old 或 new 为 ɛ，或 old 不在 feasible_inputs 中时抛出 [`Error::IllegalArgument`]
+pub(crate) fn rename_input(adjacency_matrix: &mut BTreeMap<State, BTreeMap<State, Edge>>,
+                           feasible_inputs: &mut BTreeSet<Input>,
+                           old: &Input, new: &Input) -> IResult<()> {
+    if old.is_epsilon() || new.is_epsilon() {
+        return Err(Error::IllegalArgument("ɛ cannot be renamed."));
+    }
+    if !feasible_inputs.remove(old) {
+        return Err(Error::IllegalArgument("The input does not exist."));
+    }
+    feasible_inputs.insert(new.clone());
+    for edge in adjacency_matrix.values_mut().flat_map(|to_map| to_map.values_mut()) {
+        if edge.input_set.remove(old) {
+            edge.input_set.insert(new.clone());
+        }
+    }
+    Ok(())
+}
+
+/// 将邻接矩阵中的状态 old (作为弧的起点或终点) 替换为 new，调用者需保证 new 不在邻接矩阵中
+pub(crate) fn rename_state(adjacency_matrix: &mut BTreeMap<State, BTreeMap<State, Edge>>, old: &State, new: &State) {
+    let rename = |s: State| if s == *old { new.clone() } else { s };
+    *adjacency_matrix = std::mem::take(adjacency_matrix).into_iter()
+        .map(|(from_state, to_map)| (rename(from_state), to_map.into_iter()
+            .map(|(to_state, edge)| (rename(to_state), edge))
+            .collect()))
+        .collect();
+}
+
+/// 消耗邻接矩阵，逐个产生其中的转换规则 (from_state, input, to_state)
+pub(crate) fn into_transitions(adjacency_matrix: BTreeMap<State, BTreeMap<State, Edge>>) -> impl Iterator<Item = (State, Input, State)> {
+    adjacency_matrix.into_iter().flat_map(|(from_state, to_map)| {
+        to_map.into_iter().flat_map(move |(to_state, edge)| {
+            let from_state = from_state.clone();
+            edge.input_set.into_iter().map(move |input| (from_state.clone(), input, to_state.clone()))
+        })
+    })
+}
+
+/// 返回邻接矩阵中从 state_id 出发的所有转换 (输入, 目标状态)，state_id 不存在时返回空的 `Vec`
+pub(crate) fn transitions_from(adjacency_matrix: &BTreeMap<State, BTreeMap<State, Edge>>, state_id: &str) -> Vec<(Input, State)> {
+    adjacency_matrix.get(&State::new(state_id))
+        .into_iter()
+        .flatten()
+        .flat_map(|(to_state, edge)| edge.input_set.iter().map(move |input| (input.clone(), to_state.clone())))
+        .collect()
+}
+
+/// 判断从同一状态出发的弧 to_map 中，是否有两个有重叠的输入转换到不同的状态，即该状态上的转换是否不确定
+pub(crate) fn has_conflicting_rules(to_map: &BTreeMap<State, Edge>) -> bool {
+    let rules = to_map.iter()
+        .flat_map(|(to_state, edge)| edge.input_set.iter().map(move |input| (input, to_state)))
+        .collect::<Vec<_>>();
+    rules.iter().enumerate().any(|(i, (input, to_state))| rules[i + 1..].iter()
+        .any(|(other_input, other_to_state)| to_state != other_to_state && input.overlaps(other_input)))
+}
+
+/// 检查邻接矩阵中每条弧的终点都是邻接矩阵的 key，否则抛出 [`Error::StateNotFound`]
+pub(crate) fn check_targets(adjacency_matrix: &BTreeMap<State, BTreeMap<State, Edge>>) -> IResult<()> {
+    match adjacency_matrix.values()
+        .flat_map(|to_map| to_map.keys())
+        .find(|s| !adjacency_matrix.contains_key(*s)) {
+        Some(s) => Err(Error::StateNotFound(s.state_id.clone())),
+        None => Ok(()),
+    }
+}
+
+/// 用 Tarjan 算法求出邻接矩阵所表示的图 (忽略弧上的输入) 的所有强连通分量，按逆拓扑序返回
+pub(crate) fn strongly_connected_components(adjacency_matrix: &BTreeMap<State, BTreeMap<State, Edge>>) -> Vec<BTreeSet<State>> {
+    let mut tarjan = Tarjan {
+        adjacency_matrix,
+        index: BTreeMap::new(),
+        low_link: BTreeMap::new(),
+        stack: Vec::new(),
+        on_stack: BTreeSet::new(),
+        components: Vec::new(),
+    };
+    for s in adjacency_matrix.keys() {
+        if !tarjan.index.contains_key(s) {
+            tarjan.visit(s);
+        }
+    }
+    tarjan.components
+}
+
+/// Tarjan 算法的搜索状态
+struct Tarjan<'a> {
+    adjacency_matrix: &'a BTreeMap<State, BTreeMap<State, Edge>>,
+    /// 每个状态被访问的次序
+    index: BTreeMap<&'a State, usize>,
+    /// 每个状态经搜索树及至多一条返祖弧能到达的最小次序
+    low_link: BTreeMap<&'a State, usize>,
+    stack: Vec<&'a State>,
+    on_stack: BTreeSet<&'a State>,
+    components: Vec<BTreeSet<State>>,
+}
+
+impl<'a> Tarjan<'a> {
+    fn visit(&mut self, s: &'a State) {
+        let index = self.index.len();
+        self.index.insert(s, index);
+        self.low_link.insert(s, index);
+        self.stack.push(s);
+        self.on_stack.insert(s);
+        for to_state in self.adjacency_matrix.get(s).into_iter().flat_map(|to_map| to_map.keys()) {
+            if !self.index.contains_key(to_state) {
+                self.visit(to_state);
+                let low_link = self.low_link[s].min(self.low_link[to_state]);
+                self.low_link.insert(s, low_link);
+            } else if self.on_stack.contains(to_state) {
+                let low_link = self.low_link[s].min(self.index[to_state]);
+                self.low_link.insert(s, low_link);
+            }
+        }
+        // s 是一个强连通分量的根，栈中 s 及其上方的状态构成该分量
+        if self.low_link[s] == index {
+            let mut component = BTreeSet::new();
+            while let Some(t) = self.stack.pop() {
+                self.on_stack.remove(t);
+                component.insert(t.clone());
+                if t == s {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
     }
 }
 
@@ -30,4 +204,32 @@ impl Debug for Edge {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_set().entries(self.input_set.iter()).finish()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_test() {
+        let edge = Edge::with_inputs([Input::range('a', 'z')]);
+        assert!(edge.contains_input("m"));
+        assert!(!edge.contains_input("0"));
+        assert!(!edge.contains_input("mm"));
+        assert!(edge.covers(&Input::new("m")));
+        assert!(edge.covers(&Input::range('h', 'm')));
+        assert!(!edge.covers(&Input::range('0', 'm')));
+        // ɛ 不会被任何区间匹配
+        assert!(!Edge::with_inputs([Input::range('\0', char::MAX)]).contains_input("ɛ"));
+    }
+
+    #[test]
+    fn contains_input_ignore_case_test() {
+        let edge = Edge::with_inputs([Input::new("if"), Input::range('A', 'F')]);
+        assert!(!edge.contains_input("IF"));
+        assert!(edge.contains_input_ignore_case("IF"));
+        assert!(edge.contains_input_ignore_case("iF"));
+        assert!(edge.contains_input_ignore_case("c"));
+        assert!(!edge.contains_input_ignore_case("g"));
+    }
+}
@@ -1,24 +1,291 @@
-use std::fmt::{Debug, Formatter};
+use std::collections::BTreeSet;
+use std::fmt::{Debug, Display, Formatter};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
+/// 弧上的输入，可以是单个输入字符、字符区间、通配符或取反的字符类
+/// 输入字符即自动机一步转换所读入的符号，不必是单个 `char`，例如可以用 `"if"`、`"while"` 这样的记号作为输入字符
 #[derive(PartialOrd, Ord, PartialEq, Eq, Clone, Hash)]
-pub struct Input {
-    input_str: String,
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(untagged))]
+pub enum Input {
+    /// 单个输入字符，可以由多个 `char` 组成，ɛ 也用它表示
+    Char(String),
+    /// 闭区间 [start, end] 中的任一字符
+    Range(char, char),
+    /// 通配符，匹配除 ɛ 外的任一输入字符，显示为 `.`
+    /// 字面的 `.` 仍然是普通的输入字符 `Input::new(".")`，两者互不影响
+    Any,
+    /// 取反的字符类，匹配除 ɛ 与 excluded 中的输入字符外的任一输入字符，显示为 `[^abc]`
+    /// multi_char_only 为真时只匹配由多个 `char` 组成的输入字符，显示为 `<^if,else>`，用于表示字符区间以外的其余输入字符
+    Not {
+        excluded: BTreeSet<String>,
+        #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "std::ops::Not::not"))]
+        multi_char_only: bool,
+    },
 }
 
 impl Input {
     pub fn new(input_str: impl Into<String>) -> Self {
-        Input {
-            input_str: input_str.into(),
+        Input::Char(input_str.into())
+    }
+
+    /// 创建一个匹配闭区间 [start, end] 中任一字符的输入
+    pub fn range(start: char, end: char) -> Self {
+        Input::Range(start, end)
+    }
+
+    /// 创建一个匹配任一输入字符的通配符
+    pub fn any() -> Self {
+        Input::Any
+    }
+
+    /// 创建一个匹配除 excluded 中的输入字符外任一输入字符的取反字符类，如正则表达式中的 `[^abc]`
+    pub fn not<I, S>(excluded: I) -> Self
+        where I: IntoIterator<Item = S>, S: Into<String> {
+        Input::Not { excluded: excluded.into_iter().map(Into::into).collect(), multi_char_only: false }
+    }
+
+    /// 判断是否为 ɛ
+    pub fn is_epsilon(&self) -> bool {
+        matches!(self, Input::Char(s) if s == "ɛ")
+    }
+
+    /// 判断字符 c 能否匹配当前输入
+    pub fn matches(&self, c: char) -> bool {
+        match self {
+            Input::Char(s) => {
+                let mut chars = s.chars();
+                chars.next() == Some(c) && chars.next().is_none()
+            },
+            Input::Range(start, end) => *start <= c && c <= *end,
+            Input::Any => true,
+            Input::Not { excluded, multi_char_only } => !multi_char_only && !excluded.contains(&c.to_string()),
+        }
+    }
+
+    /// 判断输入字符 input_str 能否匹配当前输入，ɛ 只与 ɛ 自身匹配
+    pub fn matches_str(&self, input_str: &str) -> bool {
+        match self {
+            Input::Char(s) => s == input_str,
+            Input::Range(..) => {
+                let mut chars = input_str.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => input_str != "ɛ" && self.matches(c),
+                    _ => false,
+                }
+            },
+            Input::Any => input_str != "ɛ",
+            Input::Not { excluded, multi_char_only } => input_str != "ɛ"
+                && (!multi_char_only || input_str.chars().nth(1).is_some())
+                && !excluded.contains(input_str),
+        }
+    }
+
+    /// 判断能被 other 匹配的输入字符是否都能被当前输入匹配
+    pub fn covers(&self, other: &Input) -> bool {
+        match (self, other) {
+            (_, Input::Char(s)) => self.matches_str(s),
+            (Input::Any, _) => true,
+            (Input::Range(start, end), Input::Range(other_start, other_end)) =>
+                other_start > other_end || (start <= other_start && other_end <= end),
+            (Input::Not { excluded, multi_char_only }, Input::Range(start, end)) =>
+                start > end || (!multi_char_only && !excluded.iter().any(|s| other.matches_str(s))),
+            (Input::Not { excluded, multi_char_only }, Input::Any) => excluded.is_empty() && !multi_char_only,
+            (Input::Not { excluded, multi_char_only }, Input::Not { multi_char_only: other_multi_char_only, .. }) =>
+                (!multi_char_only || *other_multi_char_only) && !excluded.iter().any(|s| other.matches_str(s)),
+            (Input::Range(..), Input::Any | Input::Not { .. }) | (Input::Char(_), _) => false,
+        }
+    }
+
+    /// 判断是否有输入字符能同时被当前输入与 other 匹配
+    pub fn overlaps(&self, other: &Input) -> bool {
+        match (self, other) {
+            (Input::Char(s), _) => other.matches_str(s),
+            (_, Input::Char(s)) => self.matches_str(s),
+            (Input::Range(start, end), Input::Range(other_start, other_end)) =>
+                start <= end && other_start <= other_end && start <= other_end && other_start <= end,
+            (Input::Range(start, end), Input::Any) | (Input::Any, Input::Range(start, end)) => start <= end,
+            // 取反字符类只排除有限个输入字符，区间中的字符比被排除的字符多即有重叠
+            (Input::Range(start, end), Input::Not { excluded, multi_char_only })
+            | (Input::Not { excluded, multi_char_only }, Input::Range(start, end)) => !multi_char_only
+                && range_len(*start, *end) > excluded.iter().filter(|s| Input::Range(*start, *end).matches_str(s)).count() as u64,
+            (Input::Any | Input::Not { .. }, Input::Any | Input::Not { .. }) => true,
+        }
+    }
+
+    /// 忽略大小写地判断输入字符 input_str 能否匹配当前输入
+    pub fn matches_str_ignore_case(&self, input_str: &str) -> bool {
+        match self {
+            Input::Char(s) => s.to_lowercase() == input_str.to_lowercase(),
+            Input::Range(..) => self.matches_str(input_str)
+                || self.matches_str(&input_str.to_lowercase())
+                || self.matches_str(&input_str.to_uppercase()),
+            Input::Any => self.matches_str(input_str),
+            Input::Not { excluded, multi_char_only } => input_str != "ɛ"
+                && (!multi_char_only || input_str.chars().nth(1).is_some())
+                && !excluded.iter().any(|s| s.to_lowercase() == input_str.to_lowercase()),
+        }
+    }
+
+    /// 将单个输入字符与取反字符类中的输入字符转为小写，字符区间与通配符保持不变
+    pub fn to_lowercase(&self) -> Input {
+        match self {
+            Input::Char(s) => Input::Char(s.to_lowercase()),
+            Input::Not { excluded, multi_char_only } => Input::Not {
+                excluded: excluded.iter().map(|s| s.to_lowercase()).collect(),
+                multi_char_only: *multi_char_only,
+            },
+            Input::Range(..) | Input::Any => self.clone(),
+        }
+    }
+
+    /// 返回一个能被当前输入匹配的输入字符，字符区间返回其起点，通配符返回 `.`，取反字符类返回第一个未被排除的可打印 ASCII 字符，
+    /// 只匹配多字符输入字符的取反字符类返回第一个未被排除的 `..`、`...` 等
+    pub fn representative(&self) -> String {
+        match self {
+            Input::Char(s) => s.clone(),
+            Input::Range(start, _) => start.to_string(),
+            Input::Any => ".".to_string(),
+            Input::Not { excluded, multi_char_only: true } => (2..).map(|n| ".".repeat(n))
+                .find(|s| !excluded.contains(s))
+                .unwrap(),
+            Input::Not { excluded, multi_char_only: false } => (' '..='~').chain('\u{80}'..=char::MAX)
+                .map(String::from)
+                .find(|s| !excluded.contains(s))
+                .unwrap_or_default(),
         }
     }
+}
+
+/// 将若干 (可能重叠的) 字符区间切分成互不相交的字符类，使每个类要么完全落在某个区间中，要么与它不相交
+/// 只返回被至少一个区间覆盖的类，按字符顺序排列；只含一个字符的类写作输入字符，其余写作字符区间
+pub(crate) fn disjoint_classes(ranges: &[(char, char)]) -> Vec<Input> {
+    // 每个区间的起点与终点的下一个字符都是类的边界
+    let mut bounds = BTreeSet::new();
+    for &(start, end) in ranges.iter().filter(|(start, end)| start <= end) {
+        bounds.insert(start);
+        bounds.extend(next_char(end));
+    }
+    let bounds = bounds.into_iter().collect::<Vec<_>>();
+    let mut classes = Vec::new();
+    for (i, &start) in bounds.iter().enumerate() {
+        if !ranges.iter().any(|&(s, e)| s <= start && start <= e) {
+            continue;
+        }
+        let end = bounds.get(i + 1).map_or(char::MAX, |&next| prev_char(next).unwrap());
+        classes.push(if start == end { Input::new(start) } else { Input::range(start, end) });
+    }
+    classes
+}
+
+/// 将一组 (可能相互重叠的) 输入切分成互不相交的输入类，使其中每个输入都恰好是若干个类的并
+/// 由多个 `char` 组成的输入字符各自为一类，单个字符、字符区间与取反字符类所排除的字符由 [`disjoint_classes`] 切分；
+/// 有通配符或取反字符类时，再加上代表其余所有输入字符的类：没有字符区间时为排除前面所有类的取反字符类 (前面没有任何类时为通配符)，
+/// 否则为字符区间之间的空隙，以及排除前面所有多字符输入字符、只匹配多字符输入字符的取反字符类，不会逐个列出区间中的字符
+pub(crate) fn alphabet_classes<'a, I>(inputs: I) -> BTreeSet<Input>
+    where I: IntoIterator<Item = &'a Input> {
+    let mut classes = BTreeSet::new();
+    let mut symbols = Vec::new();
+    let mut ranges = Vec::new();
+    let mut has_other = false;
+    for input in inputs {
+        match input {
+            Input::Char(s) => symbols.push(s.as_str()),
+            Input::Range(start, end) => ranges.push((*start, *end)),
+            Input::Any => has_other = true,
+            Input::Not { excluded, .. } => {
+                symbols.extend(excluded.iter().map(String::as_str));
+                has_other = true;
+            },
+        }
+    }
+    for s in symbols.into_iter().filter(|s| *s != "ɛ") {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => ranges.push((c, c)),
+            _ => {
+                classes.insert(Input::new(s));
+            },
+        }
+    }
+    let char_classes = disjoint_classes(&ranges);
+    if has_other {
+        let multi_chars = classes.iter().map(Input::representative).collect::<BTreeSet<_>>();
+        if char_classes.iter().all(|class| matches!(class, Input::Char(_))) {
+            let mut excluded = multi_chars;
+            excluded.extend(char_classes.iter().map(Input::representative));
+            classes.insert(if excluded.is_empty() { Input::Any } else { Input::not(excluded) });
+        } else {
+            classes.extend(gap_classes(&char_classes));
+            classes.insert(Input::Not { excluded: multi_chars, multi_char_only: true });
+        }
+    }
+    classes.extend(char_classes);
+    classes
+}
 
-    pub fn get_str(&self) -> &str {
-        self.input_str.as_str()
+/// 返回不被 classes 中任何类覆盖的字符组成的类，classes 须是 [`disjoint_classes`] 的结果，即按字符顺序排列且互不相交
+fn gap_classes(classes: &[Input]) -> Vec<Input> {
+    let mut gaps = Vec::new();
+    let mut next = Some('\0'); // 下一个可能未被覆盖的字符
+    for class in classes {
+        let (start, end) = match class {
+            Input::Range(start, end) => (*start, *end),
+            Input::Char(s) => {
+                let c = s.chars().next().unwrap();
+                (c, c)
+            },
+            Input::Any | Input::Not { .. } => unreachable!("disjoint_classes only returns chars and ranges"),
+        };
+        if let Some(gap_start) = next.filter(|c| *c < start) {
+            gaps.push((gap_start, prev_char(start).unwrap()));
+        }
+        next = next_char(end);
+    }
+    gaps.extend(next.map(|c| (c, char::MAX)));
+    gaps.into_iter()
+        .map(|(start, end)| if start == end { Input::new(start) } else { Input::range(start, end) })
+        .collect()
+}
+
+/// 返回闭区间 [start, end] 中的字符个数，不计代理对区间
+fn range_len(start: char, end: char) -> u64 {
+    if start > end {
+        return 0;
+    }
+    let surrogates = if (start as u32) < 0xD800 && (end as u32) > 0xDFFF { 0x800 } else { 0 };
+    (end as u64) - (start as u64) + 1 - surrogates
+}
+
+/// 返回 c 之后的第一个字符，跳过代理对区间
+fn next_char(c: char) -> Option<char> {
+    (c as u32 + 1..=char::MAX as u32).find_map(char::from_u32)
+}
+
+/// 返回 c 之前的第一个字符，跳过代理对区间
+fn prev_char(c: char) -> Option<char> {
+    (0..c as u32).rev().find_map(char::from_u32)
+}
+
+impl Display for Input {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Input::Char(s) => write!(f, "{}", s),
+            Input::Range(start, end) => write!(f, "[{}-{}]", start, end),
+            Input::Any => write!(f, "."),
+            Input::Not { excluded, multi_char_only: true } =>
+                write!(f, "<^{}>", excluded.iter().map(String::as_str).collect::<Vec<_>>().join(",")),
+            Input::Not { excluded, multi_char_only: false } => {
+                // 都是单个字符时按正则表达式的写法紧挨着写出，否则以 `,` 分隔
+                let separator = if excluded.iter().all(|s| s.chars().count() == 1) { "" } else { "," };
+                write!(f, "[^{}]", excluded.iter().map(String::as_str).collect::<Vec<_>>().join(separator))
+            },
+        }
     }
 }
 
 impl Debug for Input {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.input_str)
+        write!(f, "{}", self)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+use std::iter::Peekable;
+use std::str::Chars;
+use crate::result::{Error, IResult};
+
+/// 极简的 JSON 值，数字、布尔值与 null 只做语法检查，不保留其值
+pub(crate) enum Json {
+    String(String),
+    Array(Vec<Json>),
+    Object(BTreeMap<String, Json>),
+    Null,
+    Literal,
+}
+
+impl Json {
+    /// 解析一个 JSON 文本
+    pub(crate) fn parse(s: &str) -> IResult<Json> {
+        let mut parser = Parser { chars: s.chars().peekable(), depth: 0 };
+        let value = parser.parse_value()?;
+        parser.skip_whitespace();
+        if parser.chars.next().is_some() {
+            return Err(Error::IllegalArgument("Unexpected trailing characters after JSON value."));
+        }
+        Ok(value)
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_array(&self) -> Option<&Vec<Json>> {
+        match self {
+            Json::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_object(&self) -> Option<&BTreeMap<String, Json>> {
+        match self {
+            Json::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+}
+
+/// 将 s 写为带引号的 JSON 字符串
+pub(crate) fn quote(s: &str) -> String {
+    let mut ret = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            '\n' => ret.push_str("\\n"),
+            '\r' => ret.push_str("\\r"),
+            '\t' => ret.push_str("\\t"),
+            c if (c as u32) < 0x20 => ret.push_str(&format!("\\u{:04x}", c as u32)),
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+/// 数组与对象的最大嵌套层数，避免解析过深的输入时栈溢出
+const MAX_DEPTH: usize = 128;
+
+struct Parser<'p> {
+    chars: Peekable<Chars<'p>>,
+    /// 当前所在的数组与对象的嵌套层数
+    depth: usize,
+}
+
+impl<'p> Parser<'p> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn expect(&mut self, expected: char, error: &'static str) -> IResult<()> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            _ => Err(Error::IllegalArgument(error)),
+        }
+    }
+
+    fn parse_value(&mut self) -> IResult<Json> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some('"') => Ok(Json::String(self.parse_string()?)),
+            Some('[') => self.nested(Self::parse_array),
+            Some('{') => self.nested(Self::parse_object),
+            Some(_) => self.parse_literal(),
+            None => Err(Error::IllegalArgument("Unexpected end of JSON input.")),
+        }
+    }
+
+    /// 进入一层数组或对象并用 parse 解析，嵌套超过 [`MAX_DEPTH`] 层时抛出 [`Error::IllegalArgument`]
+    fn nested(&mut self, parse: fn(&mut Self) -> IResult<Json>) -> IResult<Json> {
+        if self.depth == MAX_DEPTH {
+            return Err(Error::IllegalArgument("JSON nesting is too deep."));
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    /// 解析数字、布尔值或 null
+    fn parse_literal(&mut self) -> IResult<Json> {
+        let mut literal = String::new();
+        while let Some(c) = self.chars.next_if(|c| c.is_ascii_alphanumeric() || "+-.".contains(*c)) {
+            literal.push(c);
+        }
+        let is_number = literal.parse::<f64>().is_ok() && !literal.starts_with(['+', '.']);
+        if literal == "null" {
+            Ok(Json::Null)
+        } else if is_number || ["true", "false"].contains(&literal.as_str()) {
+            Ok(Json::Literal)
+        } else {
+            Err(Error::IllegalArgument("Invalid JSON value."))
+        }
+    }
+
+    fn parse_array(&mut self) -> IResult<Json> {
+        self.expect('[', "Expected '[' in JSON input.")?;
+        let mut array = Vec::new();
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&']').is_some() {
+            return Ok(Json::Array(array));
+        }
+        loop {
+            array.push(self.parse_value()?);
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Json::Array(array)),
+                _ => return Err(Error::IllegalArgument("Expected ',' or ']' in JSON array.")),
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> IResult<Json> {
+        self.expect('{', "Expected '{' in JSON input.")?;
+        let mut object = BTreeMap::new();
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&'}').is_some() {
+            return Ok(Json::Object(object));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.expect(':', "Expected ':' after key in JSON object.")?;
+            object.insert(key, self.parse_value()?);
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Json::Object(object)),
+                _ => return Err(Error::IllegalArgument("Expected ',' or '}' in JSON object.")),
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> IResult<String> {
+        self.expect('"', "Expected '\"' in JSON input.")?;
+        let mut ret = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(ret),
+                Some('\\') => match self.chars.next() {
+                    Some('"') => ret.push('"'),
+                    Some('\\') => ret.push('\\'),
+                    Some('/') => ret.push('/'),
+                    Some('b') => ret.push('\u{8}'),
+                    Some('f') => ret.push('\u{c}'),
+                    Some('n') => ret.push('\n'),
+                    Some('r') => ret.push('\r'),
+                    Some('t') => ret.push('\t'),
+                    Some('u') => ret.push(self.parse_unicode_escape()?),
+                    _ => return Err(Error::IllegalArgument("Invalid escape sequence in JSON string.")),
+                },
+                Some(c) => ret.push(c),
+                None => return Err(Error::IllegalArgument("Unterminated JSON string.")),
+            }
+        }
+    }
+
+    /// 解析 `\u` 之后的转义序列，支持代理对
+    fn parse_unicode_escape(&mut self) -> IResult<char> {
+        let high = self.parse_hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if self.chars.next() != Some('\\') || self.chars.next() != Some('u') {
+                return Err(Error::IllegalArgument("Unpaired surrogate in JSON string."));
+            }
+            let low = self.parse_hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(Error::IllegalArgument("Unpaired surrogate in JSON string."));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or(Error::IllegalArgument("Invalid unicode escape in JSON string."))
+    }
+
+    fn parse_hex4(&mut self) -> IResult<u32> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self.chars.next()
+                .and_then(|c| c.to_digit(16))
+                .ok_or(Error::IllegalArgument("Invalid unicode escape in JSON string."))?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+}
@@ -1,11 +1,14 @@
-#![feature(map_first_last)]
-#![feature(map_try_insert)]
-
 pub mod automaton;
 pub mod nfa;
 pub mod dfa;
-mod input;
+pub mod regex;
+pub mod input;
 mod state;
 mod edge;
-mod disjoint_set;
-mod result;
\ No newline at end of file
+pub mod disjoint_set;
+pub mod result;
+mod display;
+mod dot;
+mod table;
+mod json;
+mod dsl;
\ No newline at end of file
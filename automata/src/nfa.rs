@@ -1,17 +1,24 @@
-use std::borrow::Borrow;
-use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
-use std::fmt::{Debug, Formatter, write};
-use std::hash::Hash;
+use std::cell::OnceCell;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
 use std::iter::once;
-use std::ptr::NonNull;
-use bimap::BiBTreeMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use crate::automaton::FiniteAutomaton;
 use crate::dfa::DFA;
-use crate::edge::Edge;
-use crate::input::Input;
+use crate::display;
+use crate::dot;
+use crate::dsl;
+use crate::edge::{self, Edge};
+use crate::input::{self, Input};
+use crate::json::{self, Json};
 use crate::result::{Error, IResult};
 use crate::state::State;
+use crate::table;
 
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NFA {
     /// 初态集，要求非空
     pub initial_states: BTreeSet<State>,
@@ -20,9 +27,16 @@ pub struct NFA {
     /// 合法输入字符集
     pub feasible_inputs: BTreeSet<Input>,
     /// 邻接矩阵，用于存储状态转换图中的所有弧
+    /// 注意：直接修改此字段不会使 ɛ 闭包矩阵失效，修改后需要重新调用 [`NFA::calc_epsilon_closure_matrix`]
     pub adjacency_matrix: BTreeMap<State, BTreeMap<State, Edge>>,
     /// ɛ 闭包矩阵，key state s 对应的 value 为从 s 出发经任意条 ɛ 弧而能到达的任何状态集
-    pub epsilon_closure_matrix: Option<BTreeMap<State, BTreeSet<State>>>,
+    /// 在第一次使用时计算并缓存，NFA 被修改时清空。不参与序列化，反序列化后重新计算
+    /// 通过 [`NFA::epsilon_closure_matrix`] 与 [`NFA::ensure_epsilon_closure`] 访问
+    #[cfg_attr(feature = "serde", serde(skip))]
+    epsilon_closure_matrix: OnceCell<BTreeMap<State, BTreeSet<State>>>,
+    /// 是否忽略大小写，见 [`NFA::set_case_insensitive`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub case_insensitive: bool,
 }
 
 impl<'a> FiniteAutomaton<'a> for NFA {
@@ -32,7 +46,8 @@ impl<'a> FiniteAutomaton<'a> for NFA {
             finite_states: BTreeSet::new(),
             feasible_inputs: BTreeSet::new(),
             adjacency_matrix: BTreeMap::new(),
-            epsilon_closure_matrix: None,
+            epsilon_closure_matrix: OnceCell::new(),
+            case_insensitive: false,
         }
     }
 
@@ -41,6 +56,7 @@ impl<'a> FiniteAutomaton<'a> for NFA {
         initial_states.for_each(|s| {
             self.initial_states.insert(State::new(s));
         });
+        self.invalidate_epsilon_closure();
         Ok(())
     }
 
@@ -49,32 +65,55 @@ impl<'a> FiniteAutomaton<'a> for NFA {
         finite_states.for_each(|s| {
             self.finite_states.insert(State::new(s));
         });
+        self.invalidate_epsilon_closure();
         Ok(())
     }
 
-    fn add_transfer_rule(&mut self, from_state_id: &str, input_str: &str, to_state_id: &str)  -> IResult<()> {
+    fn declare_alphabet<I>(&mut self, symbols: I)
+        where I: Iterator<Item = &'a str> {
+        let case_insensitive = self.case_insensitive;
+        self.feasible_inputs.extend(symbols.filter(|s| *s != "ɛ")
+            .map(|s| if case_insensitive { Input::new(s).to_lowercase() } else { Input::new(s) }));
+    }
+
+    fn add_transfer_input(&mut self, from_state_id: &str, input: Input, to_state_id: &str)  -> IResult<()> {
         let from_state = State::new(from_state_id);
         let to_state = State::new(to_state_id);
-        let input = Input::new(input_str);
+        let input = if self.case_insensitive { input.to_lowercase() } else { input };
 
-        if input_str != "ɛ" {
+        if !input.is_epsilon() {
             self.feasible_inputs.insert(input.clone());
         }
 
         self.adjacency_matrix
             .entry(from_state)
-            .or_insert(BTreeMap::new())
+            .or_default()
             .entry(to_state.clone())
-            .and_modify(|e| e.add_input(input_str))
-            .or_insert(Edge::with_inputs([input]));
+            .and_modify(|e| e.add_input(input.clone()))
+            .or_insert_with(|| Edge::with_inputs([input]));
 
-        self.adjacency_matrix.try_insert(to_state, BTreeMap::new());
+        self.adjacency_matrix.entry(to_state).or_default();
 
-        self.epsilon_closure_matrix = None;
+        self.invalidate_epsilon_closure();
 
         Ok(())
     }
 
+    fn contains_state(&self, state_id: &str) -> bool {
+        let state = State::new(state_id);
+        self.adjacency_matrix.contains_key(&state) || self.initial_states.contains(&state) || self.finite_states.contains(&state)
+    }
+
+    fn has_rule(&self, from_state_id: &str, input_str: &str, to_state_id: &str) -> bool {
+        self.adjacency_matrix.get(&State::new(from_state_id))
+            .and_then(|to_map| to_map.get(&State::new(to_state_id)))
+            .is_some_and(|edge| edge.input_set.contains(&Input::new(input_str)))
+    }
+
+    fn strongly_connected_components(&self) -> Vec<BTreeSet<State>> {
+        edge::strongly_connected_components(&self.adjacency_matrix)
+    }
+
     fn get_all_states_iter(&'a self) -> Box<dyn Iterator<Item = &'a State> + 'a> {
         Box::new(self.adjacency_matrix.keys())
     }
@@ -106,125 +145,969 @@ impl<'a> FiniteAutomaton<'a> for NFA {
 }
 
 impl NFA {
-    /// 计算 ɛ 闭包矩阵，使用 Warshall 算法
+    /// 清空已过期的 ɛ 闭包矩阵，所有修改 NFA 的方法都应调用此方法
+    fn invalidate_epsilon_closure(&mut self) {
+        self.epsilon_closure_matrix.take();
+    }
+
+    /// 计算 ɛ 闭包矩阵并缓存，即使已有缓存也会重新计算
+    /// 通常不需要手动调用，[`NFA::get_epsilon_closure`] 会在第一次使用时自动计算
     pub fn calc_epsilon_closure_matrix(&mut self) {
+        let epsilon_closure_matrix = self.compute_epsilon_closure_matrix();
+        self.epsilon_closure_matrix = OnceCell::from(epsilon_closure_matrix);
+    }
+
+    /// 返回已缓存的 ɛ 闭包矩阵，尚未计算 (或 NFA 修改后已被清空) 时返回 `None`，不会触发计算
+    pub fn epsilon_closure_matrix(&self) -> Option<&BTreeMap<State, BTreeSet<State>>> {
+        self.epsilon_closure_matrix.get()
+    }
+
+    /// 返回 ɛ 闭包矩阵，尚未计算时先计算并缓存；已有缓存时直接返回，不会重新计算
+    pub fn ensure_epsilon_closure(&mut self) -> &BTreeMap<State, BTreeSet<State>> {
+        self.epsilon_closure_matrix.get_or_init(|| self.compute_epsilon_closure_matrix())
+    }
+
+    /// 添加一条 from -> to 的 ɛ 弧，并就地更新 ɛ 闭包矩阵而不是使其失效：
+    /// 将 to 的闭包并入所有闭包含有 from 的状态的闭包中。ɛ 闭包矩阵尚未计算时，添加后完整地计算一次
+    pub fn add_epsilon_edge_incremental(&mut self, from_state_id: &str, to_state_id: &str) {
+        let epsilon_closure_matrix = self.epsilon_closure_matrix.take();
+        // NFA 的 add_transfer_rule 不会失败
+        self.add_transfer_rule(from_state_id, "ɛ", to_state_id).unwrap();
+        let mut epsilon_closure_matrix = match epsilon_closure_matrix {
+            Some(matrix) => matrix,
+            None => return self.calc_epsilon_closure_matrix(),
+        };
+        let from_state = State::new(from_state_id);
+        let to_state = State::new(to_state_id);
+        for s in [&from_state, &to_state] {
+            epsilon_closure_matrix.entry(s.clone()).or_insert_with(|| BTreeSet::from([s.clone()]));
+        }
+        let to_closure = epsilon_closure_matrix[&to_state].clone();
+        for closure in epsilon_closure_matrix.values_mut() {
+            if closure.contains(&from_state) {
+                closure.extend(to_closure.iter().cloned());
+            }
+        }
+        self.epsilon_closure_matrix = OnceCell::from(epsilon_closure_matrix);
+    }
+
+    /// 计算 ɛ 闭包矩阵，使用 Warshall 算法
+    fn compute_epsilon_closure_matrix(&self) -> BTreeMap<State, BTreeSet<State>> {
         let mut epsilon_closure_matrix = BTreeMap::new();
-        for s in self.get_all_states_iter() {
-            let mut set = BTreeSet::from_iter(self.adjacency_matrix.get(s).unwrap().iter()
+        for (s, row) in &self.adjacency_matrix {
+            let mut set = BTreeSet::from_iter(row.iter()
                 .filter(|(_,v)| v.contains_input("ɛ"))
                 .map(|(key,_)| key.to_owned()));
             set.insert(s.to_owned());
             epsilon_closure_matrix.insert(s.to_owned(), set);
         }
 
+        // 上面已为每个状态插入了一行，因此下面的 unwrap 不会失败
         for sk in self.get_all_states_iter() {
             for si in self.get_all_states_iter() {
                 for sj in self.get_all_states_iter() {
-                    if epsilon_closure_matrix.get(&si).unwrap().contains(&sk) &&
-                        epsilon_closure_matrix.get(&sk).unwrap().contains(&sj) {
-                        epsilon_closure_matrix.get_mut(&si).unwrap().insert(sj.to_owned());
+                    if epsilon_closure_matrix.get(si).unwrap().contains(sk) &&
+                        epsilon_closure_matrix.get(sk).unwrap().contains(sj) {
+                        epsilon_closure_matrix.get_mut(si).unwrap().insert(sj.to_owned());
                     }
                 }
             }
         }
 
-        self.epsilon_closure_matrix.replace(epsilon_closure_matrix);
+        // 没有任何转换规则的初态或终态不在邻接矩阵中，其 ɛ 闭包只含自身
+        for s in self.initial_states.iter().chain(self.finite_states.iter()) {
+            epsilon_closure_matrix.entry(s.to_owned())
+                .or_insert_with(|| BTreeSet::from([s.to_owned()]));
+        }
+
+        epsilon_closure_matrix
     }
 
     /// 获得一个 query_states 集的 ɛ 闭包
-    /// 注意：调用此方法前，需要先调用 [`NFA::calc_epsilon_closure_matrix`] 计算 ɛ 闭包矩阵，否则将抛出 [`Error::Uninitialized`]
+    /// 若 ɛ 闭包矩阵尚未计算 (或 NFA 修改后已被清空)，则先计算并缓存
     pub fn get_epsilon_closure<'a, I>(&self, query_states: I) -> IResult<BTreeSet<State>>
         where I: Iterator<Item = &'a State> {
-        if let Some(epsilon_closure_matrix) = &self.epsilon_closure_matrix {
-            Ok(query_states
-                    .map(|s| epsilon_closure_matrix.get(s).unwrap())
-                    .flat_map(|s| s.to_owned())
-                    .collect()
-            )
-        } else {
-            Err(Error::Uninitialized("You need to invoke NFA::calc_epsilon_closure_matrix first."))
+        let epsilon_closure_matrix = self.epsilon_closure_matrix
+            .get_or_init(|| self.compute_epsilon_closure_matrix());
+        Self::closure_from_matrix(epsilon_closure_matrix, query_states)
+    }
+
+    /// 获得单个状态 s 的 ɛ 闭包，即从 s 出发经任意条 ɛ 弧而能到达的任何状态集
+    /// 若 ɛ 闭包矩阵尚未计算，则先计算并缓存；s 不属于当前 NFA 时抛出 [`Error::StateNotFound`]
+    pub fn epsilon_reachable_from(&self, s: &State) -> IResult<&BTreeSet<State>> {
+        self.epsilon_closure_matrix
+            .get_or_init(|| self.compute_epsilon_closure_matrix())
+            .get(s)
+            .ok_or_else(|| Error::StateNotFound(s.state_id.clone()))
+    }
+
+    /// 返回从 s 出发经过一条 ɛ 弧到达的所有状态
+    fn epsilon_successors<'a>(&'a self, s: &State) -> impl Iterator<Item = &'a State> {
+        self.adjacency_matrix.get(s).into_iter()
+            .flat_map(|to_map| to_map.iter())
+            .filter(|(_, edge)| edge.contains_input("ɛ"))
+            .map(|(to_state, _)| to_state)
+    }
+
+    /// 判断 ɛ 弧构成的子图中是否存在环 (包括 ɛ 自环)，使用深度优先搜索
+    pub fn has_epsilon_cycle(&self) -> bool {
+        // 0 表示未访问，1 表示在当前搜索路径上，2 表示已完成搜索
+        let mut colors = BTreeMap::new();
+        fn has_cycle<'s>(nfa: &'s NFA, s: &'s State, colors: &mut BTreeMap<&'s State, u8>) -> bool {
+            colors.insert(s, 1);
+            for to_state in nfa.epsilon_successors(s) {
+                match colors.get(to_state) {
+                    Some(1) => return true,
+                    Some(_) => {},
+                    None => if has_cycle(nfa, to_state, colors) {
+                        return true;
+                    },
+                }
+            }
+            colors.insert(s, 2);
+            false
+        }
+        self.get_all_states_iter().any(|s| !colors.contains_key(s) && has_cycle(self, s, &mut colors))
+    }
+
+    /// 返回所有处于某个 ɛ 环上的状态，即 ɛ 子图中大小超过 1 的强连通分量或带有 ɛ 自环的状态
+    /// 状态 s 处于 ɛ 环上，当且仅当从 s 出发经过至少一条 ɛ 弧能够回到 s
+    pub fn epsilon_cycle_states(&self) -> BTreeSet<State> {
+        self.get_all_states_iter()
+            .filter(|s| {
+                let mut visited = BTreeSet::new();
+                let mut search_stack = self.epsilon_successors(s).collect::<Vec<_>>(); // 搜索栈
+                while let Some(top_state) = search_stack.pop() {
+                    if top_state == *s {
+                        return true;
+                    }
+                    if visited.insert(top_state) {
+                        search_stack.extend(self.epsilon_successors(top_state));
+                    }
+                }
+                false
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// 从 start 出发沿 ɛ 弧进行广度优先搜索，按发现顺序返回到达 ɛ 闭包中每个状态所经过的 ɛ 弧 (from, to)
+    /// 不依赖 ɛ 闭包矩阵；start 不属于当前 NFA 时抛出 [`Error::StateNotFound`]
+    pub fn epsilon_closure_detailed(&self, start: &State) -> IResult<Vec<(State, State)>> {
+        if !self.adjacency_matrix.contains_key(start)
+            && !self.initial_states.contains(start) && !self.finite_states.contains(start) {
+            return Err(Error::StateNotFound(start.state_id.clone()));
         }
+        let mut edges = Vec::new();
+        let mut visited = BTreeSet::from([start]);
+        let mut search_queue = VecDeque::from([start]); // 搜索队列
+        while let Some(front_state) = search_queue.pop_front() {
+            for to_state in self.epsilon_successors(front_state) {
+                if visited.insert(to_state) {
+                    edges.push((front_state.clone(), to_state.clone()));
+                    search_queue.push_back(to_state);
+                }
+            }
+        }
+        Ok(edges)
     }
 
-    /// 获得从 query_states 集中任一结点出发，经过一条 by_input_str 弧到达的任何状态集
-    fn straight_reachable_states<'a, I>(&self, query_states: I, by_input_str: &str) -> BTreeSet<State>
+    /// 获得一个 query_states 集的 ɛ 闭包，不会计算 ɛ 闭包矩阵
+    /// 注意：调用此方法前，需要先调用 [`NFA::calc_epsilon_closure_matrix`] 或 [`NFA::get_epsilon_closure`] 计算 ɛ 闭包矩阵，否则将抛出 [`Error::Uninitialized`]
+    pub fn try_get_epsilon_closure<'a, I>(&self, query_states: I) -> IResult<BTreeSet<State>>
+        where I: Iterator<Item = &'a State> {
+        match self.epsilon_closure_matrix.get() {
+            Some(epsilon_closure_matrix) => Self::closure_from_matrix(epsilon_closure_matrix, query_states),
+            None => Err(Error::Uninitialized("You need to invoke NFA::calc_epsilon_closure_matrix first.")),
+        }
+    }
+
+    /// 在 ɛ 闭包矩阵中查询 query_states 集的 ɛ 闭包
+    /// 若某个状态不属于当前 NFA，则抛出 [`Error::StateNotFound`]
+    fn closure_from_matrix<'a, I>(epsilon_closure_matrix: &BTreeMap<State, BTreeSet<State>>, query_states: I) -> IResult<BTreeSet<State>>
+        where I: Iterator<Item = &'a State> {
+        let mut closure = BTreeSet::new();
+        for s in query_states {
+            let row = epsilon_closure_matrix.get(s)
+                .ok_or_else(|| Error::StateNotFound(s.state_id.clone()))?;
+            closure.extend(row.iter().cloned());
+        }
+        Ok(closure)
+    }
+
+    /// 获得从 query_states 集中任一结点出发，经过一条覆盖 by_input 的弧到达的任何状态集
+    fn straight_reachable_states<'a, I>(&self, query_states: I, by_input: &Input) -> BTreeSet<State>
         where I: Iterator<Item = &'a State> {
         BTreeSet::from_iter(query_states
             .filter_map(|s| self.adjacency_matrix.get(s))
             .flat_map(|map| {
                 map.iter()
-                    .filter(|(_, v)| v.contains_input(by_input_str))
+                    .filter(|(_, v)| v.covers(by_input) || match by_input {
+                        Input::Char(s) => self.case_insensitive && v.contains_input_ignore_case(s),
+                        Input::Range(..) | Input::Any | Input::Not { .. } => false,
+                    })
                     .map(|(k, _)| k.to_owned())
             }))
     }
 
-    /// 将一个 NFA 转换为 DFA
-    fn to_dfa(& self) -> NFA {
-        let mut dfa = NFA::new();
-        let start_state = self.get_epsilon_closure(self.initial_states.iter()).unwrap();
+    /// 设置是否忽略大小写。开启后，之后加入的输入字符会被转为小写，
+    /// 并且 [`NFA::accepts`]、[`NFA::trace`] 等模拟方法忽略大小写地匹配输入字符；已有的弧不会被改写
+    pub fn set_case_insensitive(&mut self, v: bool) {
+        self.case_insensitive = v;
+    }
+
+    /// 对每个状态，返回从它出发恰好经过一条 input 弧 (不经过 ɛ 弧) 到达的状态集
+    pub fn reachable_on(&self, input: &str) -> BTreeMap<State, BTreeSet<State>> {
+        let input = Input::new(input);
+        self.get_all_states_iter()
+            .map(|s| (s.clone(), self.straight_reachable_states(once(s), &input)))
+            .collect()
+    }
+
+    /// 返回从 state 出发的所有转换 (输入, 目标状态)，按目标状态排序，弧上的每个输入单独列出；state 不存在时返回空的 `Vec`
+    pub fn transitions_from(&self, state: &str) -> Vec<(Input, State)> {
+        edge::transitions_from(&self.adjacency_matrix, state)
+    }
+
+    /// 返回所有指向 target 的弧，每个元素为 (弧的起点, 弧上的输入集)，按起点排序
+    pub fn edges_to(&self, target: &str) -> Vec<(State, BTreeSet<Input>)> {
+        let target = State::new(target);
+        self.adjacency_matrix.iter()
+            .filter_map(|(from_state, to_map)| to_map.get(&target)
+                .map(|edge| (from_state.clone(), edge.input_set.clone())))
+            .collect()
+    }
+
+    /// 判断输入串 `input` 能否被当前 NFA 接受，`input` 中每个元素为一个输入字符
+    pub fn accepts<'a, I>(&self, input: I) -> IResult<bool>
+        where I: Iterator<Item = &'a str> {
+        let mut current_states = self.get_epsilon_closure(self.initial_states.iter())?;
+        for input_str in input {
+            let j = self.straight_reachable_states(current_states.iter(), &Input::new(input_str));
+            current_states = self.get_epsilon_closure(j.iter())?;
+        }
+        Ok(current_states.iter().any(|s| self.finite_states.contains(s)))
+    }
+
+    /// 为所有状态的 id 加上前缀 prefix，用于在组合两个 NFA 时避免状态 id 冲突
+    fn with_prefix(self, prefix: &str) -> NFA {
+        self.map_states(|s| State::new(format!("{}{}", prefix, s.state_id)))
+    }
+
+    /// 将所有状态重新命名为 ids 生成的新 id，用于在组合多个 NFA 时避免状态 id 冲突
+    fn with_ids(self, ids: &mut StateIdGen) -> NFA {
+        let new_ids = self.adjacency_matrix.keys()
+            .chain(self.initial_states.iter())
+            .chain(self.finite_states.iter())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|s| (s.clone(), State::new(ids.fresh())))
+            .collect::<BTreeMap<_, _>>();
+        self.map_states(|s| new_ids[&s].clone())
+    }
+
+    /// 按 rename 重命名所有状态，rename 须是单射
+    fn map_states<F>(self, rename: F) -> NFA
+        where F: Fn(State) -> State {
+        NFA {
+            initial_states: self.initial_states.into_iter().map(&rename).collect(),
+            finite_states: self.finite_states.into_iter().map(&rename).collect(),
+            feasible_inputs: self.feasible_inputs,
+            adjacency_matrix: self.adjacency_matrix.into_iter()
+                .map(|(from_state, to_map)| {
+                    (rename(from_state), to_map.into_iter()
+                        .map(|(to_state, edge)| (rename(to_state), edge))
+                        .collect())
+                })
+                .collect(),
+            epsilon_closure_matrix: OnceCell::new(),
+            case_insensitive: self.case_insensitive,
+        }
+    }
+
+    /// 将 other 的所有弧与合法输入字符并入当前 NFA，要求两者的状态 id 互不相同
+    /// 初态集与终态集保持不变。由于 other 的邻接矩阵会整行覆盖，须在连接新的 ɛ 弧之前调用
+    fn merge_rules(&mut self, other: NFA) {
+        self.feasible_inputs.extend(other.feasible_inputs);
+        self.adjacency_matrix.extend(other.adjacency_matrix);
+        self.invalidate_epsilon_closure();
+    }
+
+    /// 添加从 from_states 中每个状态到 to_states 中每个状态的 ɛ 弧
+    fn add_epsilon_rules(&mut self, from_states: &BTreeSet<State>, to_states: &BTreeSet<State>) {
+        for from_state in from_states {
+            for to_state in to_states {
+                self.add_transfer_rule(&from_state.state_id, "ɛ", &to_state.state_id).unwrap();
+            }
+        }
+    }
+
+    /// 连接两个 NFA，得到的 NFA 接受 L(self)L(other)
+    /// 两个 NFA 的状态 id 分别加上前缀 `0.` 与 `1.`，self 的每个终态经 ɛ 弧到达 other 的每个初态
+    pub fn concat(self, other: NFA) -> NFA {
+        self.with_prefix("0.").concat_disjoint(other.with_prefix("1."))
+    }
+
+    /// 与 [`NFA::concat`] 相同，但两个 NFA 的所有状态都重新命名为 ids 生成的新 id
+    pub fn concat_with_ids(self, other: NFA, ids: &mut StateIdGen) -> NFA {
+        let nfa = self.with_ids(ids);
+        nfa.concat_disjoint(other.with_ids(ids))
+    }
+
+    /// 连接两个状态 id 互不相同的 NFA
+    fn concat_disjoint(mut self, other: NFA) -> NFA {
+        let (other_initial_states, other_finite_states) = (other.initial_states.clone(), other.finite_states.clone());
+        self.merge_rules(other);
+        let finite_states = std::mem::replace(&mut self.finite_states, other_finite_states);
+        self.add_epsilon_rules(&finite_states, &other_initial_states);
+        self
+    }
+
+    /// 合并两个 NFA，得到的 NFA 接受 L(self) ∪ L(other)
+    /// 两个 NFA 的状态 id 分别加上前缀 `0.` 与 `1.`，并新增唯一的初态 `start` 与唯一的终态 `accept`
+    pub fn union(self, other: NFA) -> NFA {
+        self.with_prefix("0.").union_disjoint(other.with_prefix("1."), State::new("start"), State::new("accept"))
+    }
+
+    /// 与 [`NFA::union`] 相同，但两个 NFA 的所有状态以及新增的初态与终态都使用 ids 生成的新 id
+    pub fn union_with_ids(self, other: NFA, ids: &mut StateIdGen) -> NFA {
+        let nfa = self.with_ids(ids);
+        let other = other.with_ids(ids);
+        nfa.union_disjoint(other, State::new(ids.fresh()), State::new(ids.fresh()))
+    }
+
+    /// 合并两个状态 id 互不相同的 NFA，start 与 accept 为新增的初态与终态
+    fn union_disjoint(mut self, other: NFA, start: State, accept: State) -> NFA {
+        let start = BTreeSet::from([start]);
+        let accept = BTreeSet::from([accept]);
+        let mut initial_states = std::mem::replace(&mut self.initial_states, start.clone());
+        let mut finite_states = std::mem::replace(&mut self.finite_states, accept.clone());
+        initial_states.extend(other.initial_states.iter().cloned());
+        finite_states.extend(other.finite_states.iter().cloned());
+        self.merge_rules(other);
+        self.add_epsilon_rules(&start, &initial_states);
+        self.add_epsilon_rules(&finite_states, &accept);
+        self
+    }
+
+    /// 求 NFA 的闭包，得到的 NFA 接受 L(self)*
+    /// 原状态 id 加上前缀 `0.`，并新增唯一的初态 `start` 与唯一的终态 `accept`
+    pub fn star(self) -> NFA {
+        self.with_prefix("0.").wrap(State::new("start"), State::new("accept"), true, true)
+    }
+
+    /// 与 [`NFA::star`] 相同，但所有状态以及新增的初态与终态都使用 ids 生成的新 id
+    pub fn star_with_ids(self, ids: &mut StateIdGen) -> NFA {
+        let nfa = self.with_ids(ids);
+        nfa.wrap(State::new(ids.fresh()), State::new(ids.fresh()), true, true)
+    }
+
+    /// 求 NFA 的正闭包，得到的 NFA 接受 L(self)+，即 L(self) 中的串重复至少一次
+    /// 与 [`NFA::star`] 相同，但没有从 `start` 直接到达 `accept` 的 ɛ 弧
+    pub fn plus(self) -> NFA {
+        self.with_prefix("0.").wrap(State::new("start"), State::new("accept"), true, false)
+    }
+
+    /// 与 [`NFA::plus`] 相同，但所有状态以及新增的初态与终态都使用 ids 生成的新 id
+    pub fn plus_with_ids(self, ids: &mut StateIdGen) -> NFA {
+        let nfa = self.with_ids(ids);
+        nfa.wrap(State::new(ids.fresh()), State::new(ids.fresh()), true, false)
+    }
+
+    /// 求 NFA 的可选形式，得到的 NFA 接受 L(self) ∪ {ɛ}
+    /// 原状态 id 加上前缀 `0.`，并新增唯一的初态 `start` 与唯一的终态 `accept`，`start` 经 ɛ 弧直接到达 `accept`
+    pub fn optional(self) -> NFA {
+        self.with_prefix("0.").wrap(State::new("start"), State::new("accept"), false, true)
+    }
+
+    /// 与 [`NFA::optional`] 相同，但所有状态以及新增的初态与终态都使用 ids 生成的新 id
+    pub fn optional_with_ids(self, ids: &mut StateIdGen) -> NFA {
+        let nfa = self.with_ids(ids);
+        nfa.wrap(State::new(ids.fresh()), State::new(ids.fresh()), false, true)
+    }
+
+    /// 以新增的 start 与 accept 为唯一的初态与终态包住当前 NFA，二者须不是已有的状态
+    /// repeat 为真时原终态经 ɛ 弧回到原初态，skip 为真时 start 经 ɛ 弧直接到达 accept
+    fn wrap(mut self, start: State, accept: State, repeat: bool, skip: bool) -> NFA {
+        let start = BTreeSet::from([start]);
+        let accept = BTreeSet::from([accept]);
+        let initial_states = std::mem::replace(&mut self.initial_states, start.clone());
+        let finite_states = std::mem::replace(&mut self.finite_states, accept.clone());
+        self.add_epsilon_rules(&start, &initial_states);
+        if skip {
+            self.add_epsilon_rules(&start, &accept);
+        }
+        if repeat {
+            self.add_epsilon_rules(&finite_states, &initial_states);
+        }
+        self.add_epsilon_rules(&finite_states, &accept);
+        self
+    }
+
+    /// 添加一条从 from_state_id 到一个新状态的转换规则，新状态的 id 由 ids 生成，返回新状态的 id
+    pub fn add_fresh_transfer_input(&mut self, ids: &mut StateIdGen, from_state_id: &str, input: Input) -> String {
+        let to_state_id = ids.fresh();
+        // NFA 的 add_transfer_input 不会失败
+        self.add_transfer_input(from_state_id, input, &to_state_id).unwrap();
+        to_state_id
+    }
+
+    /// 从教科书式的转换表构造 NFA，rows 中每个元素为一条转换规则 (from_state, input, to_state)
+    pub fn from_table(initial: &str, finite: &[&str], rows: &[(&str, &str, &str)]) -> IResult<NFA> {
+        let mut nfa = NFA::new();
+        nfa.add_initial_states(once(initial))?;
+        nfa.add_finite_states(finite.iter().copied())?;
+        for (from_state_id, input_str, to_state_id) in rows {
+            nfa.add_transfer_rule(from_state_id, input_str, to_state_id)?;
+        }
+        Ok(nfa)
+    }
+
+    /// 将当前 NFA 导出为 JSON，格式为
+    /// ```json
+    /// {
+    ///     "initial": ["X"],
+    ///     "finite": ["Y"],
+    ///     "rules": [{"from": "X", "input": "ɛ", "to": "Y"}]
+    /// }
+    /// ```
+    /// 其中 `initial` 与 `finite` 分别为初态集与终态集，`rules` 中每个对象为一条转换规则，
+    /// 字符区间形式的 `input` 写作由起点与终点组成的数组，如 `["a", "z"]`，通配符写作 `null`，
+    /// 取反字符类写作 `{"excluded": ["a", "b"]}`，只匹配多字符输入字符的取反字符类写作 `{"multi_char_excluded": ["if"]}`
+    pub fn to_json(&self) -> String {
+        let states_json = |states: &BTreeSet<State>| states.iter()
+            .map(|s| json::quote(&s.state_id))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let input_json = |input: &Input| match input {
+            Input::Char(s) => json::quote(s),
+            Input::Range(start, end) => format!("[{}, {}]", json::quote(&start.to_string()), json::quote(&end.to_string())),
+            Input::Any => "null".to_string(),
+            Input::Not { excluded, multi_char_only } => format!("{{\"{}\": [{}]}}",
+                if *multi_char_only { "multi_char_excluded" } else { "excluded" },
+                excluded.iter().map(|s| json::quote(s)).collect::<Vec<_>>().join(", ")),
+        };
+        let rules_json = self.adjacency_matrix.iter()
+            .flat_map(|(from_state, to_map)| to_map.iter().flat_map(move |(to_state, edge)| {
+                edge.input_set.iter().map(move |input| format!("\n        {{\"from\": {}, \"input\": {}, \"to\": {}}}",
+                    json::quote(&from_state.state_id), input_json(input), json::quote(&to_state.state_id)))
+            }))
+            .collect::<Vec<_>>()
+            .join(",");
+        format!("{{\n    \"initial\": [{}],\n    \"finite\": [{}],\n    \"rules\": [{}\n    ]\n}}",
+            states_json(&self.initial_states), states_json(&self.finite_states), rules_json)
+    }
+
+    /// 从 [`NFA::to_json`] 所述格式的 JSON 导入 NFA
+    /// 格式错误或数组与对象嵌套超过 128 层时抛出 [`Error::IllegalArgument`]，并指出出错的字段
+    pub fn from_json(s: &str) -> IResult<NFA> {
+        let json = Json::parse(s)?;
+        let object = json.as_object()
+            .ok_or(Error::IllegalArgument("The JSON document must be an object."))?;
+        let string_array = |field: &str, error: &'static str| -> IResult<Vec<&str>> {
+            object.get(field)
+                .and_then(Json::as_array)
+                .ok_or(Error::IllegalArgument(error))?
+                .iter()
+                .map(|s| s.as_str().ok_or(Error::IllegalArgument(error)))
+                .collect()
+        };
+        let initial_states = string_array("initial", "Field `initial` must be an array of strings.")?;
+        let finite_states = string_array("finite", "Field `finite` must be an array of strings.")?;
+        let rules = object.get("rules")
+            .and_then(Json::as_array)
+            .ok_or(Error::IllegalArgument("Field `rules` must be an array of objects."))?;
+
+        let single_char = |json: &Json| json.as_str().and_then(|s| {
+            let mut chars = s.chars();
+            chars.next().filter(|_| chars.next().is_none())
+        });
+
+        let mut nfa = NFA::new();
+        nfa.add_initial_states(initial_states.into_iter())?;
+        nfa.add_finite_states(finite_states.into_iter())?;
+        for rule in rules {
+            let rule = rule.as_object()
+                .ok_or(Error::IllegalArgument("Field `rules` must be an array of objects."))?;
+            let from_state_id = rule.get("from").and_then(Json::as_str)
+                .ok_or(Error::IllegalArgument("Field `rules[].from` must be a string."))?;
+            let input = rule.get("input").and_then(|input| match input {
+                Json::String(s) => Some(Input::new(s.as_str())),
+                Json::Array(range) => match range.as_slice() {
+                    [start, end] => Some(Input::range(single_char(start)?, single_char(end)?)),
+                    _ => None,
+                },
+                Json::Null => Some(Input::any()),
+                Json::Object(class) => {
+                    let multi_char_only = class.contains_key("multi_char_excluded");
+                    class.get(if multi_char_only { "multi_char_excluded" } else { "excluded" })
+                        .and_then(Json::as_array)?
+                        .iter()
+                        .map(|s| s.as_str().map(String::from))
+                        .collect::<Option<BTreeSet<_>>>()
+                        .map(|excluded| Input::Not { excluded, multi_char_only })
+                },
+                _ => None,
+            }).ok_or(Error::IllegalArgument("Field `rules[].input` must be a string, an array of two characters, null or an object with `excluded` or `multi_char_excluded`."))?;
+            let to_state_id = rule.get("to").and_then(Json::as_str)
+                .ok_or(Error::IllegalArgument("Field `rules[].to` must be a string."))?;
+            nfa.add_transfer_input(from_state_id, input, to_state_id)?;
+        }
+        Ok(nfa)
+    }
+
+    /// 从逐行的文本描述导入 NFA，格式为
+    /// ```text
+    /// # 注释
+    /// initial: X
+    /// final: Y Z
+    /// X -a-> Y
+    /// X -ɛ-> Z
+    /// ```
+    /// 其中 `initial:` 与 `final:` 后为以空白分隔的状态，可以出现多次；其余每行为一条转换规则，
+    /// 弧上的输入写作 `.` (通配符)、`[a-z]` (字符区间)、`[^abc]` (取反字符类)、`<^if,else>` (只匹配多字符输入字符的取反字符类) 或普通的输入字符，以 `\` 开头时其余部分为字面的输入字符。
+    /// 状态与输入中的空白写作 `\s`、`\t`、`\n`、`\r` 或 `\u{3000}`，空串写作 `\e`，`\#` 与 `\:` 为字面的 `#` 与 `:`，`\\` 为字面的 `\`。
+    /// 以 `#` 开头的行与空行被忽略，格式错误时抛出带有行号的 [`Error::IllegalLine`]
+    pub fn from_dsl(src: &str) -> IResult<NFA> {
+        let document = dsl::parse(src)?;
+        let mut nfa = NFA::new();
+        nfa.add_initial_states(document.initial_states.iter().map(String::as_str))?;
+        nfa.add_finite_states(document.finite_states.iter().map(String::as_str))?;
+        for (from_state_id, input, to_state_id) in document.rules {
+            nfa.add_transfer_input(&from_state_id, input, &to_state_id)?;
+        }
+        Ok(nfa)
+    }
+
+    /// 将当前 NFA 写为 [`NFA::from_dsl`] 所述格式的文本描述，状态与转换规则均排好序，输出稳定，便于比较与纳入版本控制
+    /// 弧上的多个输入各写为一行；没有任何弧的非初态、非终态状态不会出现在结果中
+    pub fn to_dsl(&self) -> String {
+        dsl::to_dsl(&self.initial_states, &self.finite_states, &self.adjacency_matrix)
+    }
+
+    /// 删除一条转换规则，弧上不再有任何输入时删除整条弧，已没有任何弧使用的输入会从 `feasible_inputs` 中删除
+    /// 起点或终点不存在时抛出 [`Error::StateNotFound`]，规则不存在时抛出 [`Error::IllegalArgument`]
+    pub fn remove_transfer_rule(&mut self, from_state_id: &str, input_str: &str, to_state_id: &str) -> IResult<()> {
+        edge::remove_transfer_rule(&mut self.adjacency_matrix, &mut self.feasible_inputs,
+                                   from_state_id, &Input::new(input_str), to_state_id)?;
+        self.invalidate_epsilon_closure();
+        Ok(())
+    }
+
+    /// 将所有弧上的输入字符 old 重命名为 new，并更新 `feasible_inputs`
+    /// 若某条弧上已有 new，则 old 与之合并；old 或 new 为 ɛ，或 old 不是合法输入字符时抛出 [`Error::IllegalArgument`]
+    pub fn rename_input(&mut self, old: &str, new: &str) -> IResult<()> {
+        edge::rename_input(&mut self.adjacency_matrix, &mut self.feasible_inputs, &Input::new(old), &Input::new(new))?;
+        self.invalidate_epsilon_closure();
+        Ok(())
+    }
+
+    /// 消耗当前 NFA，按起点、终点、输入的顺序逐个产生其中的转换规则 (from_state, input, to_state)
+    /// 状态与输入直接从邻接矩阵中移出；没有任何弧的状态不会出现在结果中
+    pub fn into_transitions(self) -> impl Iterator<Item = (State, Input, State)> {
+        edge::into_transitions(self.adjacency_matrix)
+    }
+
+    /// 返回当前 NFA 中转换规则的数量，即不同的 (from_state, input, to_state) 三元组的数量
+    pub fn num_edges(&self) -> usize {
+        self.adjacency_matrix.values()
+            .flat_map(|to_map| to_map.values())
+            .map(|edge| edge.input_set.len())
+            .sum()
+    }
+
+    /// 返回当前 NFA 中 ɛ 转换规则的数量
+    pub fn num_epsilon_edges(&self) -> usize {
+        self.adjacency_matrix.values()
+            .flat_map(|to_map| to_map.values())
+            .filter(|edge| edge.input_set.iter().any(Input::is_epsilon))
+            .count()
+    }
+
+    /// 返回当前 NFA 的字母表大小，即 `feasible_inputs` 中输入的数量
+    pub fn alphabet_size(&self) -> usize {
+        self.feasible_inputs.len()
+    }
+
+    /// 将所有状态重新命名为 "0", "1", ...，编号按从初态出发的广度优先搜索顺序确定
+    /// 不可达的状态按原有顺序排在最后
+    pub fn normalize_ids(&mut self) {
+        let mut order = Vec::new();
+        let mut visited = BTreeSet::from_iter(self.initial_states.iter());
+        let mut search_queue = VecDeque::from_iter(self.initial_states.iter()); // 搜索队列
+        while let Some(front_state) = search_queue.pop_front() {
+            order.push(front_state);
+            for to_state in self.adjacency_matrix.get(front_state).into_iter().flat_map(|map| map.keys()) {
+                if visited.insert(to_state) {
+                    search_queue.push_back(to_state);
+                }
+            }
+        }
+        order.extend(self.get_all_states_iter().chain(self.finite_states.iter()).filter(|s| visited.insert(s)));
+        let new_ids = order.into_iter().enumerate()
+            .map(|(i, s)| (s.clone(), State::new(i.to_string())))
+            .collect::<BTreeMap<_, _>>();
+
+        self.initial_states = self.initial_states.iter().map(|s| new_ids[s].clone()).collect();
+        self.finite_states = self.finite_states.iter().map(|s| new_ids[s].clone()).collect();
+        self.adjacency_matrix = std::mem::take(&mut self.adjacency_matrix).into_iter()
+            .map(|(from_state, to_map)| (new_ids[&from_state].clone(), to_map.into_iter()
+                .map(|(to_state, edge)| (new_ids[&to_state].clone(), edge))
+                .collect()))
+            .collect();
+        self.invalidate_epsilon_closure();
+    }
+
+    /// 将状态 old 重命名为 new，包括邻接矩阵中的行与弧的终点、初态集与终态集
+    /// old 不存在时抛出 [`Error::StateNotFound`]；new 已存在时抛出 [`Error::IllegalArgument`]，以免意外合并两个状态
+    pub fn rename_state(&mut self, old: &str, new: &str) -> IResult<()> {
+        if !self.contains_state(old) {
+            return Err(Error::StateNotFound(old.to_string()));
+        }
+        if self.contains_state(new) {
+            return Err(Error::IllegalArgument("The new state id already exists."));
+        }
+        let (old, new) = (State::new(old), State::new(new));
+        edge::rename_state(&mut self.adjacency_matrix, &old, &new);
+        for states in [&mut self.initial_states, &mut self.finite_states] {
+            if states.remove(&old) {
+                states.insert(new.clone());
+            }
+        }
+        self.invalidate_epsilon_closure();
+        Ok(())
+    }
+
+    /// 将当前 NFA 渲染为状态转换表，列为排好序的 `feasible_inputs`，存在 ɛ 弧时最后一列为 ε
+    /// 初态以 `->` 标记，终态以 `*` 标记，单元格中可能有多个以 `,` 分隔的目标状态
+    pub fn to_table(&self) -> String {
+        let epsilon = Input::new("ɛ");
+        let mut inputs = self.feasible_inputs.iter().cloned().collect::<Vec<_>>();
+        if self.adjacency_matrix.values().flat_map(|to_map| to_map.values()).any(|e| e.input_set.contains(&epsilon)) {
+            inputs.push(epsilon);
+        }
+        table::to_table(&self.initial_states, &self.finite_states, &inputs, &self.adjacency_matrix)
+    }
+
+    /// 将当前 NFA 渲染为 Graphviz DOT 格式
+    pub fn to_dot(&self) -> String {
+        dot::to_dot(self.initial_states.iter(), &self.finite_states, &self.adjacency_matrix)
+    }
+
+    /// 逐步执行输入串 `input`，返回读入每个输入字符前、以及读入最后一个输入字符后的活动状态集 (已取 ɛ 闭包)
+    /// 不会计算 ɛ 闭包矩阵，尚未计算时抛出 [`Error::Uninitialized`]，见 [`NFA::try_get_epsilon_closure`]
+    pub fn trace(&self, input: &[&str]) -> IResult<Vec<BTreeSet<State>>> {
+        let mut current_states = self.try_get_epsilon_closure(self.initial_states.iter())?;
+        let mut steps = Vec::with_capacity(input.len() + 1);
+        for input_str in input {
+            let j = self.straight_reachable_states(current_states.iter(), &Input::new(*input_str));
+            let next_states = self.try_get_epsilon_closure(j.iter())?;
+            steps.push(std::mem::replace(&mut current_states, next_states));
+        }
+        steps.push(current_states);
+        Ok(steps)
+    }
+
+    /// 消除 ɛ 转换，得到一个等价的、不含 ɛ 弧的 NFA
+    /// 对每个状态 s 与每个输入 a，从 s 向 ɛ-closure(move(ɛ-closure(s), a)) 中的每个状态添加一条 a 弧；
+    /// 若 ɛ-closure(s) 中含有终态，则 s 为终态。初态与 `feasible_inputs` 保持不变
+    pub fn remove_epsilon(&self) -> NFA {
+        let mut nfa = NFA::new();
+        nfa.initial_states = self.initial_states.clone();
+        nfa.feasible_inputs = self.feasible_inputs.clone();
+        nfa.case_insensitive = self.case_insensitive;
+        let states = self.get_all_states_iter()
+            .chain(self.initial_states.iter())
+            .chain(self.finite_states.iter())
+            .collect::<BTreeSet<_>>();
+        for s in states {
+            // 查询的都是当前 NFA 中的状态，因此 unwrap 不会失败
+            let closure = self.get_epsilon_closure(once(s)).unwrap();
+            if !closure.is_disjoint(&self.finite_states) {
+                nfa.finite_states.insert(s.clone());
+            }
+            for input in &self.feasible_inputs {
+                let j = self.straight_reachable_states(closure.iter(), input);
+                for to_state in self.get_epsilon_closure(j.iter()).unwrap() {
+                    nfa.add_transfer_input(&s.state_id, input.clone(), &to_state.state_id).unwrap();
+                }
+            }
+        }
+        nfa
+    }
+
+    /// 检查当前 NFA 的结构不变式：初态集非空，并且每条弧的终点都是邻接矩阵的 key
+    /// 用于检查直接修改字段构造的 NFA，在第一处违反时抛出相应的错误
+    pub fn validate(&self) -> IResult<()> {
+        if self.initial_states.is_empty() {
+            return Err(Error::IllegalArgument("NFA has no initial states"));
+        }
+        edge::check_targets(&self.adjacency_matrix)
+    }
+
+    /// 判断当前 NFA 是否已经是确定的：恰有一个初态、没有 ɛ 弧，并且每个状态经每个输入字符至多转换到一个状态，
+    /// 即同一状态上转换到不同状态的输入互不重叠
+    pub fn is_deterministic(&self) -> bool {
+        self.initial_states.len() == 1
+            && self.num_epsilon_edges() == 0
+            && !self.adjacency_matrix.values().any(edge::has_conflicting_rules)
+    }
+
+    /// 将一个确定的 NFA 直接复制为 DFA，不做子集构造；不满足 [`NFA::is_deterministic`] 时抛出 [`Error::UnsupportedOperation`]
+    pub fn as_dfa(&self) -> IResult<DFA> {
+        if !self.is_deterministic() {
+            return Err(Error::UnsupportedOperation("The NFA is not deterministic."));
+        }
+        let mut dfa = DFA::new();
+        dfa.initial_state = self.initial_states.first().cloned();
+        dfa.finite_states = self.finite_states.clone();
+        dfa.feasible_inputs = self.feasible_inputs.clone();
+        dfa.adjacency_matrix = self.adjacency_matrix.clone();
+        dfa.case_insensitive = self.case_insensitive;
+        Ok(dfa)
+    }
+
+    /// 将一个 NFA 转换为 DFA，`feasible_inputs` 先被切分为互不相交的输入类，每个类作为 DFA 的一个输入：
+    /// 重叠的字符区间被切分开，通配符与取反字符类只覆盖其余的输入字符
+    /// 转换到空状态集的输入不会生成转换规则，得到的 DFA 可能不完全，见 [`DFA::complete`]
+    /// 初态集为空时抛出 [`Error::IllegalArgument`]；`feasible_inputs` 中混入了 ɛ 时抛出 [`Error::UnsupportedOperation`]；
+    /// 不会计算 ɛ 闭包矩阵，尚未计算时抛出 [`Error::Uninitialized`]
+    pub fn to_dfa(&self) -> IResult<DFA> {
+        Ok(self.to_dfa_with_mapping()?.0)
+    }
+
+    /// 将一个 NFA 直接转换为最小的 DFA：必要时先计算 ɛ 闭包矩阵，再依次调用 [`NFA::to_dfa`] 与 [`DFA::minimize`]
+    /// 可能抛出的错误与 [`NFA::to_dfa`] 相同，但不会抛出 [`Error::Uninitialized`]
+    pub fn to_minimal_dfa(&self) -> IResult<DFA> {
+        self.epsilon_closure_matrix.get_or_init(|| self.compute_epsilon_closure_matrix());
+        Ok(self.to_dfa()?.minimize())
+    }
+
+    /// 与 [`NFA::to_dfa`] 相同，但 DFA 的状态数超过 max_states 时放弃已完成的部分，
+    /// 并抛出 [`Error::UnsupportedOperation`]，以防止子集构造的状态数爆炸
+    pub fn to_dfa_capped(&self, max_states: usize) -> IResult<DFA> {
+        Ok(self.determinize(Some(max_states))?.0)
+    }
+
+    /// 将一个 NFA 转换为 DFA，同时返回 DFA 中每个状态所代表的原 NFA 状态集，可能抛出的错误与 [`NFA::to_dfa`] 相同
+    pub fn to_dfa_with_mapping(&self) -> IResult<(DFA, BTreeMap<State, BTreeSet<State>>)> {
+        self.determinize(None)
+    }
+
+    /// 子集构造，max_states 不为 `None` 时限制 DFA 的状态数
+    /// DFA 的状态名各不相同且不含 ɛ 转换，因此下面的 unwrap 都不会失败
+    fn determinize(&self, max_states: Option<usize>) -> IResult<(DFA, BTreeMap<State, BTreeSet<State>>)> {
+        if self.initial_states.is_empty() {
+            return Err(Error::IllegalArgument("NFA has no initial states"));
+        }
+        // 直接修改 feasible_inputs 可能混入 ɛ，它会成为 DFA 的一个输入，导致 DFA 中出现 ɛ 弧
+        if self.feasible_inputs.iter().any(Input::is_epsilon) {
+            return Err(Error::UnsupportedOperation("ɛ must not be in feasible_inputs"));
+        }
+        if max_states == Some(0) {
+            return Err(Error::UnsupportedOperation("state limit exceeded"));
+        }
+        let mut dfa = DFA::new();
+        dfa.set_case_insensitive(self.case_insensitive);
+        // 在互不相交的输入类上构造，使 DFA 的每个状态经每个输入字符至多有一条弧可走
+        let inputs = input::alphabet_classes(&self.feasible_inputs);
+        let start_state = self.try_get_epsilon_closure(self.initial_states.iter())?;
         let mut search_queue = VecDeque::new(); // 搜索队列
         let mut known_states = BTreeMap::new(); // 保存所有已知的状态
         // 初始状态入队
         search_queue.push_back(start_state.clone());
         known_states.insert(start_state, "0".to_string());
-        dfa.add_initial_states(once("0"));
+        dfa.add_initial_states(once("0")).unwrap();
         // 循环直至搜索队列为空
         while let Some(front_state) = search_queue.pop_front() { // 取出队首 front_state
             let new_front_state_id = known_states.get(&front_state).unwrap().to_owned();
+            // 如果当前状态含有原终态，则是新的终态
+            if self.finite_states.iter().any(|s| front_state.contains(s)) {
+                dfa.add_finite_states(once(new_front_state_id.as_str())).unwrap();
+            }
             // 计算从 front_state 接受 input 所转换到的状态
-            for input in &self.feasible_inputs {
-                let j = self.straight_reachable_states(front_state.iter(), input.get_str());
-                let transfered_state = self.get_epsilon_closure(j.iter()).unwrap();
+            for input in &inputs {
+                let j = self.straight_reachable_states(front_state.iter(), input);
+                let transfered_state = self.try_get_epsilon_closure(j.iter())?;
+                // 空状态集即隐式的死状态，不为其添加状态与转换规则
+                if transfered_state.is_empty() {
+                    continue;
+                }
                 // 如果这一状态没有被计算过，则将其加入搜索队列
                 if !known_states.contains_key(&transfered_state) {
+                    if max_states.is_some_and(|max_states| known_states.len() >= max_states) {
+                        return Err(Error::UnsupportedOperation("state limit exceeded"));
+                    }
                     search_queue.push_back(transfered_state.clone());
                 }
                 // 更新已知状态
                 let num_known_states = known_states.len();
-                let transfered_state_id = known_states.entry(transfered_state.clone())
+                let transfered_state_id = known_states.entry(transfered_state)
                     .or_insert( num_known_states.to_string());
                 // 添加一条转换规则
-                dfa.add_transfer_rule(&new_front_state_id, input.get_str(), &transfered_state_id);
-                // 如果当前状态含有原终态，则是新的终态
-                if self.finite_states.iter().any(|s| {
-                    transfered_state.contains(s)
-                }) {
-                    dfa.add_finite_states(once(transfered_state_id.as_str()));
-                }
+                dfa.add_transfer_input(&new_front_state_id, input.clone(), transfered_state_id).unwrap();
             }
         }
-        println!("{:?}", known_states);
-        return dfa;
+        let mapping = known_states.into_iter()
+            .map(|(nfa_states, state_id)| (State::new(state_id), nfa_states))
+            .collect();
+        Ok((dfa, mapping))
+    }
+}
+
+/// NFA 的构造器，以链式调用的方式描述初态、终态与转换规则，最后由 [`NfaBuilder::build`] 生成 NFA
+#[derive(Default)]
+pub struct NfaBuilder {
+    initial_states: Vec<String>,
+    finite_states: Vec<String>,
+    rules: Vec<(String, String, String)>,
+}
+
+impl NfaBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加一个初态
+    pub fn initial(mut self, state_id: &str) -> Self {
+        self.initial_states.push(state_id.to_string());
+        self
+    }
+
+    /// 添加一个终态
+    pub fn finite(mut self, state_id: &str) -> Self {
+        self.finite_states.push(state_id.to_string());
+        self
+    }
+
+    /// 添加一条转换规则
+    pub fn rule(mut self, from_state_id: &str, input_str: &str, to_state_id: &str) -> Self {
+        self.rules.push((from_state_id.to_string(), input_str.to_string(), to_state_id.to_string()));
+        self
+    }
+
+    /// 生成 NFA，初态集为空时抛出 [`Error::IllegalArgument`]
+    pub fn build(self) -> IResult<NFA> {
+        if self.initial_states.is_empty() {
+            return Err(Error::IllegalArgument("An NFA needs at least one initial state."));
+        }
+        let mut nfa = NFA::new();
+        nfa.add_initial_states(self.initial_states.iter().map(String::as_str))?;
+        nfa.add_finite_states(self.finite_states.iter().map(String::as_str))?;
+        for (from_state_id, input_str, to_state_id) in &self.rules {
+            nfa.add_transfer_rule(from_state_id, input_str, to_state_id)?;
+        }
+        Ok(nfa)
+    }
+}
+
+/// 状态 id 生成器，依次生成 `q0`, `q1`, ...，保证同一生成器给出的 id 互不相同
+/// 在构造多个片段再组合的场合 (如 Thompson 构造) 共用一个生成器，即可避免状态 id 冲突
+#[derive(Debug, Clone)]
+pub struct StateIdGen {
+    prefix: String,
+    next: usize,
+}
+
+impl StateIdGen {
+    pub fn new() -> Self {
+        Self::with_prefix("q")
+    }
+
+    /// 创建一个以 prefix 为前缀的生成器，prefix 为空串时直接生成 `0`, `1`, ...
+    pub fn with_prefix(prefix: &str) -> Self {
+        StateIdGen {
+            prefix: prefix.to_string(),
+            next: 0,
+        }
+    }
+
+    /// 生成一个新的状态 id
+    pub fn fresh(&mut self) -> String {
+        self.next += 1;
+        format!("{}{}", self.prefix, self.next - 1)
+    }
+}
+
+impl Default for StateIdGen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 比较的是结构而非语言，不考虑缓存的 ɛ 闭包矩阵
+impl PartialEq for NFA {
+    fn eq(&self, other: &Self) -> bool {
+        self.initial_states == other.initial_states
+            && self.finite_states == other.finite_states
+            && self.feasible_inputs == other.feasible_inputs
+            && self.adjacency_matrix == other.adjacency_matrix
+            && self.case_insensitive == other.case_insensitive
+    }
+}
+
+impl Eq for NFA {}
+
+impl Hash for NFA {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.initial_states.hash(state);
+        self.finite_states.hash(state);
+        self.feasible_inputs.hash(state);
+        self.adjacency_matrix.hash(state);
+        self.case_insensitive.hash(state);
     }
 }
 
 impl Debug for NFA {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut ret = String::new();
-        ret.push_str("FiniteAutomaton {\n    initial_states: ");
-        ret.push_str(&format!("{:?}", self.initial_states));
-        ret.push_str("\n    finite_states: ");
-        ret.push_str(&format!("{:?}", self.finite_states));
-        ret.push_str("\n    transfer_rules: ");
-        for (from_state, to_map) in &self.adjacency_matrix {
-            for (to_state, edge) in to_map {
-                ret.push_str(&format!("\n        {:?} => {:?} => {:?}", from_state, edge, to_state));
-            }
+        display::fmt_automaton(f, "initial_states", &self.initial_states, &self.finite_states, &self.adjacency_matrix)
+    }
+}
+
+impl Display for NFA {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        display::fmt_automaton(f, "initial_states", &self.initial_states, &self.finite_states, &self.adjacency_matrix)
+    }
+}
+
+/// 将 DFA 视为 NFA：唯一的初态成为只含一个元素的初态集，终态集与邻接矩阵保持不变，ɛ 闭包矩阵尚未计算
+impl From<DFA> for NFA {
+    fn from(dfa: DFA) -> Self {
+        NFA {
+            initial_states: dfa.initial_state.into_iter().collect(),
+            finite_states: dfa.finite_states,
+            feasible_inputs: dfa.feasible_inputs,
+            adjacency_matrix: dfa.adjacency_matrix,
+            epsilon_closure_matrix: OnceCell::new(),
+            case_insensitive: dfa.case_insensitive,
         }
-        ret.push_str("\n}");
-        write!(f, "{}", ret)
     }
 }
 
+/// 以声明式的语法构造 NFA，返回 `IResult<NFA>`，任一步骤出错时返回该错误
+/// ```
+/// use automata::nfa;
+/// let nfa = nfa!(
+///     initial_states: "X";
+///     finite_states: "Y";
+///     transfer_rules: "X" => "a" => "Y",
+///                     "Y" => "ɛ" => "X"
+/// ).unwrap();
+/// assert!(nfa.accepts(["a", "a"].into_iter()).unwrap());
+/// ```
+#[macro_export]
 macro_rules! nfa {
     (initial_states: $($initial_state: expr),* ;
      finite_states: $($finite_state: expr),* ;
      transfer_rules: $($from_state: expr => $input: expr => $to_state: expr),*) => {{
-        let mut nfa = NFA::new();
-        $(nfa.add_initial_states(once($initial_state));)*
-        $(nfa.add_finite_states(once($finite_state));)*
-        $(nfa.add_transfer_rule($from_state, $input, $to_state);)*
-        nfa
+        use $crate::automaton::FiniteAutomaton;
+        (|| -> $crate::result::IResult<$crate::nfa::NFA> {
+            let mut nfa = $crate::nfa::NFA::new();
+            $(nfa.add_initial_states(::std::iter::once($initial_state))?;)*
+            $(nfa.add_finite_states(::std::iter::once($finite_state))?;)*
+            $(nfa.add_transfer_rule($from_state, $input, $to_state)?;)*
+            Ok(nfa)
+        })()
     }};
 }
 
@@ -247,10 +1130,962 @@ mod tests {
                             "2" => "ɛ" => "6",
                             "6" => "a" => "6",
                             "6" => "b" => "6",
-                            "6" => "ɛ" => "Y");
-        println!("{:#?}", nfa);
+                            "6" => "ɛ" => "Y").unwrap();
+        assert_eq!(nfa.get_states_num(), 8);
         nfa.calc_epsilon_closure_matrix();
-        let dfa = nfa.to_dfa();
-        println!("{:?}", dfa);
+        let dfa = nfa.to_dfa().unwrap();
+        assert!(dfa.accepts(&["b", "a", "a"]));
+        assert!(!dfa.accepts(&["a", "b", "a"]));
+    }
+
+    /// 返回 alphabet 上所有长度不超过 max_len 的串
+    fn all_words<'a>(alphabet: &[&'a str], max_len: usize) -> Vec<Vec<&'a str>> {
+        let mut words = vec![vec![]];
+        let mut last = vec![vec![]];
+        for _ in 0..max_len {
+            last = last.iter()
+                .flat_map(|w: &Vec<&'a str>| alphabet.iter().map(move |c| [w.as_slice(), &[*c]].concat()))
+                .collect();
+            words.extend(last.iter().cloned());
+        }
+        words
+    }
+
+    #[test]
+    fn remove_epsilon_test() {
+        let nfa = example_nfa();
+        let epsilon_free = nfa.remove_epsilon();
+        assert_eq!(epsilon_free.num_epsilon_edges(), 0);
+        assert_eq!(epsilon_free.initial_states, nfa.initial_states);
+        for word in all_words(&["a", "b"], 5) {
+            assert_eq!(epsilon_free.accepts(word.iter().copied()).unwrap(),
+                       nfa.accepts(word.iter().copied()).unwrap(), "{:?}", word);
+        }
+        // ɛ 能到达终态的初态成为终态
+        assert!(single("ɛ").remove_epsilon().accepts([].into_iter()).unwrap());
+    }
+
+    #[test]
+    fn to_dfa_test() {
+        let mut nfa = nfa!(
+            initial_states: "X";
+            finite_states: "Y";
+            transfer_rules: "X" => "a" => "X",
+                            "X" => "b" => "X",
+                            "X" => "a" => "Y").unwrap();
+        nfa.calc_epsilon_closure_matrix();
+        let dfa: DFA = nfa.to_dfa().unwrap();
+        assert_eq!(dfa.initial_state, Some(State::new("0")));
+        assert_eq!(dfa.get_states_num(), 2);
+        assert_eq!(dfa.finite_states, BTreeSet::from([State::new("1")]));
+    }
+
+    #[test]
+    fn to_minimal_dfa_test() {
+        let nfa = example_nfa();
+        assert!(nfa.epsilon_closure_matrix().is_none());
+        let dfa = nfa.to_minimal_dfa().unwrap();
+        // (a|b)*(aa|bb)(a|b)* 的最小 DFA：初态、刚读入 a、刚读入 b、已接受 四个状态
+        assert_eq!(dfa.get_states_num(), 4);
+        assert_eq!(dfa.finite_states.len(), 1);
+        for word in all_words(&["a", "b"], 5) {
+            assert_eq!(dfa.accepts(&word), nfa.accepts(word.iter().copied()).unwrap(), "{:?}", word);
+        }
+
+        // 初态的 ɛ 闭包没有任何出弧
+        for nfa in [crate::regex::from_regex("").unwrap(), crate::regex::from_regex("()").unwrap(),
+                    crate::regex::from_regex("|").unwrap(), single("ɛ")] {
+            let dfa = nfa.to_minimal_dfa().unwrap();
+            assert_eq!(dfa.initial_state, Some(State::new("0")));
+            assert!(dfa.accepts(&[]));
+            assert!(!dfa.accepts(&["a"]));
+        }
+        let mut nfa = NFA::new();
+        nfa.add_initial_states(once("X")).unwrap();
+        nfa.add_finite_states(once("Y")).unwrap();
+        assert!(nfa.to_minimal_dfa().unwrap().is_empty());
+    }
+
+    #[test]
+    fn to_dfa_without_dead_state_test() {
+        let mut nfa = nfa!(
+            initial_states: "X";
+            finite_states: "Z";
+            transfer_rules: "X" => "a" => "Y",
+                            "Y" => "b" => "Z").unwrap();
+        nfa.calc_epsilon_closure_matrix();
+        let dfa = nfa.to_dfa().unwrap();
+        // 不再为空状态集单独添加一个显式的死状态
+        assert_eq!(dfa.get_states_num(), 3);
+        assert_eq!(dfa.num_edges(), 2);
+        assert!(dfa.accepts(&["a", "b"]));
+        assert!(!dfa.accepts(&["b"]));
+        assert!(!dfa.is_complete());
+    }
+
+    #[test]
+    fn to_dfa_error_test() {
+        let mut nfa = NFA::new();
+        nfa.add_transfer_rule("X", "a", "Y").unwrap();
+        nfa.calc_epsilon_closure_matrix();
+        assert!(matches!(nfa.to_dfa(), Err(Error::IllegalArgument(_))));
+        let nfa = single("a");
+        assert!(matches!(nfa.to_dfa(), Err(Error::Uninitialized(_))));
+        let mut nfa = single("a");
+        nfa.calc_epsilon_closure_matrix();
+        assert!(nfa.to_dfa().unwrap().accepts(&["a"]));
+    }
+
+    #[test]
+    fn to_dfa_capped_test() {
+        let mut nfa = example_nfa();
+        nfa.calc_epsilon_closure_matrix();
+        let num_states = nfa.to_dfa().unwrap().get_states_num();
+        assert!(matches!(nfa.to_dfa_capped(2), Err(Error::UnsupportedOperation("state limit exceeded"))));
+        assert!(matches!(nfa.to_dfa_capped(num_states - 1), Err(Error::UnsupportedOperation(_))));
+        assert_eq!(nfa.to_dfa_capped(num_states).unwrap(), nfa.to_dfa().unwrap());
+        assert_eq!(nfa.to_dfa_capped(1000).unwrap(), nfa.to_dfa().unwrap());
+    }
+
+    #[test]
+    fn to_dfa_with_mapping_test() {
+        let mut nfa = example_nfa();
+        nfa.calc_epsilon_closure_matrix();
+        let (dfa, mapping) = nfa.to_dfa_with_mapping().unwrap();
+        assert_eq!(mapping.len(), dfa.get_states_num());
+        assert_eq!(mapping[&State::new("0")], nfa.get_epsilon_closure(nfa.initial_states.iter()).unwrap());
+        for (state, nfa_states) in &mapping {
+            assert_eq!(dfa.finite_states.contains(state), nfa_states.contains(&State::new("Y")));
+        }
+        // 初态本身含有原终态时也是终态
+        let mut nfa = single("ɛ");
+        nfa.calc_epsilon_closure_matrix();
+        assert!(nfa.to_dfa().unwrap().accepts(&[]));
+    }
+
+    #[test]
+    fn contains_test() {
+        let mut nfa = example_nfa();
+        assert!(nfa.contains_state("X"));
+        assert!(nfa.contains_state("6"));
+        assert!(!nfa.contains_state("7"));
+        nfa.add_finite_states(once("Z")).unwrap();
+        assert!(nfa.contains_state("Z"));
+        assert!(nfa.has_rule("X", "ɛ", "5"));
+        assert!(nfa.has_rule("1", "b", "4"));
+        assert!(!nfa.has_rule("1", "a", "4"));
+        assert!(!nfa.has_rule("4", "b", "1"));
+        assert!(!nfa.has_rule("7", "a", "X"));
+    }
+
+    #[test]
+    fn reachable_on_test() {
+        let reachable = example_nfa().reachable_on("a");
+        assert_eq!(reachable.len(), 8);
+        assert_eq!(reachable[&State::new("5")], BTreeSet::from([State::new("5")]));
+        assert_eq!(reachable[&State::new("1")], BTreeSet::from([State::new("3")]));
+        // 不计 ɛ 弧
+        assert!(reachable[&State::new("X")].is_empty());
+        assert!(reachable[&State::new("4")].is_empty());
+    }
+
+    #[test]
+    fn transitions_from_test() {
+        let nfa = example_nfa();
+        assert_eq!(nfa.transitions_from("5"), vec![
+            (Input::new("ɛ"), State::new("1")),
+            (Input::new("a"), State::new("5")),
+            (Input::new("b"), State::new("5")),
+        ]);
+        assert!(nfa.transitions_from("Y").is_empty());
+        assert!(nfa.transitions_from("Z").is_empty());
+    }
+
+    #[test]
+    fn edges_to_test() {
+        let mut nfa = example_nfa();
+        nfa.add_transfer_rule("5", "ɛ", "2").unwrap();
+        assert_eq!(nfa.edges_to("2"), vec![
+            (State::new("3"), BTreeSet::from([Input::new("a")])),
+            (State::new("4"), BTreeSet::from([Input::new("b")])),
+            (State::new("5"), BTreeSet::from([Input::new("ɛ")])),
+        ]);
+        assert_eq!(nfa.edges_to("6"), vec![
+            (State::new("2"), BTreeSet::from([Input::new("ɛ")])),
+            (State::new("6"), BTreeSet::from([Input::new("a"), Input::new("b")])),
+        ]);
+        assert!(nfa.edges_to("X").is_empty());
+    }
+
+    #[test]
+    fn case_insensitive_test() {
+        let mut nfa = single("a");
+        assert!(!nfa.accepts(once("A")).unwrap());
+        nfa.set_case_insensitive(true);
+        assert!(nfa.accepts(once("A")).unwrap());
+        assert!(nfa.accepts(once("a")).unwrap());
+        assert!(nfa.to_dfa().unwrap().accepts(&["A"]));
+        // 开启后加入的输入字符被转为小写，不会在 feasible_inputs 中重复
+        nfa.add_transfer_rule("Y", "B", "Y").unwrap();
+        nfa.add_transfer_rule("Y", "b", "Y").unwrap();
+        assert_eq!(nfa.feasible_inputs, BTreeSet::from([Input::new("a"), Input::new("b")]));
+    }
+
+    #[test]
+    fn from_dfa_test() {
+        let dfa = crate::dfa!(
+            initial_state: "0";
+            finite_states: "2";
+            transfer_rules:
+                "0" => "a" => "1", "0" => "b" => "0",
+                "1" => "a" => "1", "1" => "b" => "2",
+                "2" => "a" => "1", "2" => "b" => "0"
+        ).unwrap();
+        let mut nfa = NFA::from(dfa.clone());
+        assert_eq!(nfa.initial_states, BTreeSet::from([State::new("0")]));
+        nfa.calc_epsilon_closure_matrix();
+        for word in all_words(&["a", "b"], 4) {
+            assert_eq!(nfa.accepts(word.iter().copied()).unwrap(), dfa.accepts(&word));
+        }
+        // 转换得到的 NFA 可以参与 NFA 的组合
+        let concat = nfa.concat(single("c"));
+        assert!(concat.accepts(["a", "b", "c"].into_iter()).unwrap());
+        assert!(NFA::from(DFA::new()).initial_states.is_empty());
+    }
+
+    #[test]
+    fn add_epsilon_edge_incremental_test() {
+        let mut nfa = example_nfa();
+        nfa.calc_epsilon_closure_matrix();
+        for (from, to) in [("6", "X"), ("Y", "Z"), ("W", "5"), ("4", "3")] {
+            nfa.add_epsilon_edge_incremental(from, to);
+            let mut expected = nfa.clone();
+            expected.calc_epsilon_closure_matrix();
+            assert_eq!(nfa.epsilon_closure_matrix, expected.epsilon_closure_matrix);
+        }
+        // 尚未计算 ɛ 闭包矩阵时完整地计算一次
+        let mut nfa = example_nfa();
+        nfa.add_epsilon_edge_incremental("6", "X");
+        assert!(nfa.epsilon_closure_matrix.get().is_some());
+        assert!(nfa.epsilon_reachable_from(&State::new("6")).unwrap().contains(&State::new("X")));
+    }
+
+    #[test]
+    fn to_dfa_epsilon_input_test() {
+        let mut nfa = example_nfa();
+        nfa.calc_epsilon_closure_matrix();
+        nfa.feasible_inputs.insert(Input::new("ɛ"));
+        assert!(matches!(nfa.to_dfa(), Err(Error::UnsupportedOperation(_))));
+        assert!(matches!(nfa.to_dfa_capped(100), Err(Error::UnsupportedOperation(_))));
+        nfa.feasible_inputs.remove(&Input::new("ɛ"));
+        assert!(!nfa.to_dfa().unwrap().feasible_inputs.iter().any(Input::is_epsilon));
+    }
+
+    #[test]
+    fn rename_input_test() {
+        let mut nfa = example_nfa();
+        nfa.rename_input("a", "x").unwrap();
+        assert_eq!(nfa.feasible_inputs, BTreeSet::from([Input::new("b"), Input::new("x")]));
+        assert!(nfa.accepts(["x", "x"].into_iter()).unwrap());
+        assert!(nfa.accepts(["b", "x", "x", "b"].into_iter()).unwrap());
+        assert!(!nfa.accepts(["a", "a"].into_iter()).unwrap());
+        // 与弧上已有的输入合并
+        nfa.rename_input("b", "x").unwrap();
+        assert_eq!(nfa.feasible_inputs, BTreeSet::from([Input::new("x")]));
+        assert_eq!(nfa.adjacency_matrix[&State::new("5")][&State::new("5")].input_set, BTreeSet::from([Input::new("x")]));
+        assert!(matches!(nfa.rename_input("a", "y"), Err(Error::IllegalArgument(_))));
+        assert!(matches!(nfa.rename_input("x", "ɛ"), Err(Error::IllegalArgument(_))));
+    }
+
+    #[test]
+    fn rename_state_test() {
+        let mut nfa = example_nfa();
+        nfa.calc_epsilon_closure_matrix();
+        nfa.rename_state("X", "start").unwrap();
+        nfa.rename_state("2", "two").unwrap();
+        assert_eq!(nfa.initial_states, BTreeSet::from([State::new("start")]));
+        assert!(!nfa.contains_state("X") && !nfa.contains_state("2"));
+        assert!(nfa.has_rule("start", "ɛ", "5"));
+        assert!(nfa.has_rule("3", "a", "two") && nfa.has_rule("two", "ɛ", "6"));
+        assert_eq!(nfa.num_edges(), example_nfa().num_edges());
+        for word in all_words(&["a", "b"], 5) {
+            assert_eq!(nfa.accepts(word.iter().copied()).unwrap(),
+                       example_nfa().accepts(word.iter().copied()).unwrap(), "{:?}", word);
+        }
+        nfa.rename_state("Y", "end").unwrap();
+        assert_eq!(nfa.finite_states, BTreeSet::from([State::new("end")]));
+        assert!(matches!(nfa.rename_state("5", "6"), Err(Error::IllegalArgument(_))));
+        assert!(matches!(nfa.rename_state("X", "x"), Err(Error::StateNotFound(s)) if s == "X"));
+    }
+
+    #[test]
+    fn is_deterministic_test() {
+        let nfa = nfa!(
+            initial_states: "0";
+            finite_states: "2";
+            transfer_rules: "0" => "a" => "1",
+                            "1" => "b" => "2",
+                            "2" => "a" => "1").unwrap();
+        assert!(nfa.is_deterministic());
+        let dfa = nfa.as_dfa().unwrap();
+        assert_eq!(dfa.initial_state, Some(State::new("0")));
+        for word in all_words(&["a", "b"], 4) {
+            assert_eq!(dfa.accepts(&word), nfa.accepts(word.iter().copied()).unwrap());
+        }
+
+        // 同一输入有两个目标、含 ɛ 弧、有多个初态时都不是确定的
+        let mut nondeterministic = nfa.clone();
+        nondeterministic.add_transfer_rule("0", "a", "2").unwrap();
+        assert!(!nondeterministic.is_deterministic());
+        assert!(matches!(nondeterministic.as_dfa(), Err(Error::UnsupportedOperation(_))));
+        assert!(!example_nfa().is_deterministic());
+        let mut two_initial = nfa.clone();
+        two_initial.add_initial_states(once("1")).unwrap();
+        assert!(!two_initial.is_deterministic());
+        assert!(!NFA::new().is_deterministic());
+
+        // 通配符与取反字符类与其他输入重叠，只有转换到同一状态或互不重叠时才是确定的
+        let mut wildcard = nfa.clone();
+        wildcard.add_transfer_input("0", Input::not(["a"]), "2").unwrap();
+        assert!(wildcard.is_deterministic());
+        wildcard.add_transfer_input("0", Input::range('a', 'c'), "2").unwrap();
+        assert!(!wildcard.is_deterministic());
+    }
+
+    #[test]
+    fn plus_optional_test() {
+        let star = single("a").star();
+        let plus = single("a").plus();
+        assert!(star.accepts(std::iter::empty()).unwrap());
+        assert!(!plus.accepts(std::iter::empty()).unwrap());
+        for n in 1..4 {
+            assert!(plus.accepts(std::iter::repeat_n("a", n)).unwrap());
+        }
+        assert!(!plus.accepts(["b"].into_iter()).unwrap());
+
+        let optional = single("a").concat(single("b")).optional();
+        assert!(optional.accepts(std::iter::empty()).unwrap());
+        assert!(optional.accepts(["a", "b"].into_iter()).unwrap());
+        assert!(!optional.accepts(["a", "b", "a", "b"].into_iter()).unwrap());
+        assert!(!optional.accepts(["a"].into_iter()).unwrap());
+    }
+
+    #[test]
+    fn into_transitions_test() {
+        let nfa = example_nfa();
+        let rows = nfa.clone().into_transitions()
+            .map(|(from_state, input, to_state)| (from_state.state_id, input.to_string(), to_state.state_id))
+            .collect::<Vec<_>>();
+        assert_eq!(rows.len(), nfa.num_edges());
+        let rows = rows.iter().map(|(f, i, t)| (f.as_str(), i.as_str(), t.as_str())).collect::<Vec<_>>();
+        assert_eq!(NFA::from_table("X", &["Y"], &rows).unwrap(), nfa);
+    }
+
+    #[test]
+    fn wildcard_test() {
+        let mut nfa = NFA::new();
+        nfa.add_initial_states(once("X")).unwrap();
+        nfa.add_finite_states(once("Y")).unwrap();
+        nfa.add_transfer_input("X", Input::any(), "Y").unwrap();
+        for symbol in ["a", "b", ".", "if"] {
+            assert!(nfa.accepts(once(symbol)).unwrap(), "{}", symbol);
+        }
+        assert!(!nfa.accepts(std::iter::empty()).unwrap());
+        assert!(!nfa.accepts(["a", "b"].into_iter()).unwrap());
+        assert!(!nfa.accepts(once("ɛ")).unwrap());
+        assert_eq!(NFA::from_json(&nfa.to_json()).unwrap(), nfa);
+
+        // 子集构造在互不相交的输入类上进行，其余输入字符由 DFA 中排除了 a、b、. 的取反字符类弧处理
+        nfa.add_transfer_rule("X", "a", "Z").unwrap();
+        nfa.add_transfer_rule("Z", "b", "Y").unwrap();
+        nfa.add_transfer_rule("Y", ".", "Y").unwrap();
+        assert!(!nfa.is_deterministic());
+        nfa.calc_epsilon_closure_matrix();
+        let dfa = nfa.to_dfa().unwrap();
+        for word in all_words(&["a", "b", ".", "c"], 3) {
+            assert_eq!(dfa.accepts(&word), nfa.accepts(word.iter().copied()).unwrap(), "{:?}", word);
+        }
+        assert!(dfa.validate().is_ok());
+        assert!(dfa.feasible_inputs.contains(&Input::not(["a", "b", "."])));
+        // 字面的 . 不是通配符
+        assert!(nfa.accepts(["c", "."].into_iter()).unwrap());
+        assert!(!nfa.accepts(["c", "c"].into_iter()).unwrap());
+    }
+
+    #[test]
+    fn ensure_epsilon_closure_test() {
+        let mut nfa = example_nfa();
+        assert!(nfa.epsilon_closure_matrix().is_none());
+        let first = nfa.ensure_epsilon_closure() as *const _;
+        assert!(nfa.ensure_epsilon_closure()[&State::new("X")].contains(&State::new("1")));
+        // 第二次调用直接返回缓存，而不是重新计算
+        assert_eq!(nfa.ensure_epsilon_closure() as *const _, first);
+        assert_eq!(nfa.epsilon_closure_matrix().map(|m| m as *const _), Some(first));
+        nfa.add_transfer_rule("Y", "ɛ", "X").unwrap();
+        assert!(nfa.epsilon_closure_matrix().is_none());
+        assert!(nfa.ensure_epsilon_closure()[&State::new("Y")].contains(&State::new("1")));
+    }
+
+    #[test]
+    fn overlapping_ranges_test() {
+        let mut nfa = NFA::new();
+        nfa.add_initial_states(once("X")).unwrap();
+        nfa.add_finite_states(["A", "B"].into_iter()).unwrap();
+        nfa.add_transfer_input("X", Input::range('a', 'm'), "A").unwrap();
+        nfa.add_transfer_input("X", Input::range('h', 'z'), "B").unwrap();
+        nfa.add_transfer_rule("A", "k", "A").unwrap();
+        nfa.calc_epsilon_closure_matrix();
+        let dfa = nfa.to_dfa().unwrap();
+        // 重叠的区间被切分为互不相交的字符类，而不是逐个字符展开
+        assert_eq!(dfa.feasible_inputs, BTreeSet::from([
+            Input::range('a', 'g'),
+            Input::range('h', 'j'),
+            Input::new("k"),
+            Input::range('l', 'm'),
+            Input::range('n', 'z'),
+        ]));
+        assert_eq!(dfa.num_edges(), 7);
+        for word in all_words(&["a", "g", "h", "k", "m", "n", "z", "0"], 2) {
+            assert_eq!(dfa.accepts(&word), nfa.accepts(word.iter().copied()).unwrap(), "{:?}", word);
+        }
+    }
+
+    #[test]
+    fn validate_test() {
+        let nfa = example_nfa();
+        assert!(nfa.validate().is_ok());
+
+        let mut no_initial = nfa.clone();
+        no_initial.initial_states.clear();
+        assert!(matches!(no_initial.validate(), Err(Error::IllegalArgument(_))));
+
+        let mut dangling = nfa;
+        dangling.adjacency_matrix.remove(&State::new("Y"));
+        assert!(matches!(dangling.validate(), Err(Error::StateNotFound(s)) if s == "Y"));
+    }
+
+    #[test]
+    fn negated_class_test() {
+        let mut nfa = NFA::new();
+        nfa.add_initial_states(once("X")).unwrap();
+        nfa.add_finite_states(once("Y")).unwrap();
+        nfa.add_transfer_input("X", Input::not(["a"]), "Y").unwrap();
+        assert!(nfa.accepts(once("b")).unwrap());
+        assert!(nfa.accepts(once("c")).unwrap());
+        assert!(!nfa.accepts(once("a")).unwrap());
+        assert!(!nfa.accepts(once("ɛ")).unwrap());
+        assert_eq!(Input::not(["a", "b"]).to_string(), "[^ab]");
+        assert_eq!(NFA::from_json(&nfa.to_json()).unwrap(), nfa);
+
+        // 被排除的输入字符在子集构造中单独作为 DFA 的输入
+        nfa.add_transfer_rule("X", "b", "Z").unwrap();
+        nfa.add_transfer_rule("Z", "a", "Y").unwrap();
+        nfa.add_transfer_input("Y", Input::not(["b"]), "Y").unwrap();
+        assert!(!nfa.is_deterministic());
+        nfa.calc_epsilon_closure_matrix();
+        let dfa = nfa.to_dfa().unwrap();
+        for word in all_words(&["a", "b", "c"], 4) {
+            assert_eq!(dfa.accepts(&word), nfa.accepts(word.iter().copied()).unwrap(), "{:?}", word);
+        }
+    }
+
+    #[test]
+    fn accepts_test() {
+        let mut nfa = nfa!(
+            initial_states: "X";
+            finite_states: "Y";
+            transfer_rules: "X" => "ɛ" => "1",
+                            "1" => "ɛ" => "Y",
+                            "X" => "a" => "2",
+                            "2" => "b" => "2",
+                            "2" => "ɛ" => "Y").unwrap();
+        nfa.calc_epsilon_closure_matrix();
+        assert!(nfa.accepts([].into_iter()).unwrap());
+        assert!(nfa.accepts(["a"].into_iter()).unwrap());
+        assert!(nfa.accepts(["a", "b", "b"].into_iter()).unwrap());
+        assert!(!nfa.accepts(["b"].into_iter()).unwrap());
+        assert!(!nfa.accepts(["a", "a"].into_iter()).unwrap());
+    }
+
+    /// 测试中常用的示例 NFA，接受所有含有 aa 或 bb 子串的 {a, b} 串
+    fn example_nfa() -> NFA {
+        nfa!(
+            initial_states: "X";
+            finite_states: "Y";
+            transfer_rules: "X" => "ɛ" => "5",
+                            "5" => "a" => "5",
+                            "5" => "b" => "5",
+                            "5" => "ɛ" => "1",
+                            "1" => "a" => "3",
+                            "3" => "a" => "2",
+                            "1" => "b" => "4",
+                            "4" => "b" => "2",
+                            "2" => "ɛ" => "6",
+                            "6" => "a" => "6",
+                            "6" => "b" => "6",
+                            "6" => "ɛ" => "Y").unwrap()
+    }
+
+    /// 只接受单个输入字符 input_str 的 NFA
+    fn single(input_str: &str) -> NFA {
+        let mut nfa = NFA::new();
+        nfa.add_initial_states(once("X")).unwrap();
+        nfa.add_finite_states(once("Y")).unwrap();
+        nfa.add_transfer_rule("X", input_str, "Y").unwrap();
+        nfa
+    }
+
+    #[test]
+    fn concat_test() {
+        let mut nfa = single("a").concat(single("b"));
+        nfa.calc_epsilon_closure_matrix();
+        assert_eq!(nfa.get_states_num(), 4);
+        assert_eq!(nfa.feasible_inputs, BTreeSet::from([Input::new("a"), Input::new("b")]));
+        assert!(nfa.accepts(["a", "b"].into_iter()).unwrap());
+        assert!(!nfa.accepts(["a"].into_iter()).unwrap());
+        assert!(!nfa.accepts(["b", "a"].into_iter()).unwrap());
+    }
+
+    #[test]
+    fn union_test() {
+        let mut nfa = single("a").union(single("b"));
+        nfa.calc_epsilon_closure_matrix();
+        assert_eq!(nfa.get_states_num(), 6);
+        assert!(nfa.accepts(["a"].into_iter()).unwrap());
+        assert!(nfa.accepts(["b"].into_iter()).unwrap());
+        assert!(!nfa.accepts([].into_iter()).unwrap());
+        assert!(!nfa.accepts(["a", "b"].into_iter()).unwrap());
+    }
+
+    #[test]
+    fn star_test() {
+        let mut nfa = single("a").concat(single("b")).star();
+        nfa.calc_epsilon_closure_matrix();
+        assert!(nfa.accepts([].into_iter()).unwrap());
+        assert!(nfa.accepts(["a", "b"].into_iter()).unwrap());
+        assert!(nfa.accepts(["a", "b", "a", "b"].into_iter()).unwrap());
+        assert!(!nfa.accepts(["a"].into_iter()).unwrap());
+        assert!(!nfa.accepts(["a", "b", "b"].into_iter()).unwrap());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_test() {
+        let mut nfa = example_nfa();
+        nfa.calc_epsilon_closure_matrix();
+        let json = serde_json::to_string(&nfa).unwrap();
+        assert!(!json.contains("epsilon_closure_matrix"));
+        let mut deserialized: NFA = serde_json::from_str(&json).unwrap();
+        assert!(deserialized.epsilon_closure_matrix.get().is_none());
+        assert_eq!(format!("{:?}", deserialized), format!("{:?}", nfa));
+        assert_eq!(deserialized.feasible_inputs, nfa.feasible_inputs);
+        deserialized.calc_epsilon_closure_matrix();
+        assert!(deserialized.accepts(["b", "a", "a"].into_iter()).unwrap());
+    }
+
+    #[test]
+    fn json_test() {
+        let nfa = example_nfa();
+        let json = nfa.to_json();
+        assert!(json.contains("{\"from\": \"X\", \"input\": \"ɛ\", \"to\": \"5\"}"));
+        let imported = NFA::from_json(&json).unwrap();
+        assert_eq!(format!("{:?}", imported), format!("{:?}", nfa));
+        assert_eq!(imported.feasible_inputs, nfa.feasible_inputs);
+        assert_eq!(imported.to_json(), json);
+
+        let imported = NFA::from_json(r#"{"initial": ["\"q\u0030\""], "finite": [], "rules": []}"#).unwrap();
+        assert_eq!(imported.initial_states, BTreeSet::from([State::new("\"q0\"")]));
+        assert!(NFA::from_json(&imported.to_json()).is_ok());
+    }
+
+    #[test]
+    fn from_dsl_test() {
+        let nfa = NFA::from_dsl("
+            # 以 ab 结尾的串
+            initial: X
+            final: Y
+
+            X -ɛ-> 1
+            1 -a-> 1
+            1 -b-> 1
+            1 -a-> 2
+            2 -b-> Y
+            Y -[0-9]-> Y
+            Y -.-> Z
+            Z -[^xy]-> Z
+            Z -\\.-> Y
+        ").unwrap();
+        assert_eq!(nfa.initial_states, BTreeSet::from([State::new("X")]));
+        assert_eq!(nfa.finite_states, BTreeSet::from([State::new("Y")]));
+        assert_eq!(nfa.transitions_from("1"), vec![
+            (Input::new("a"), State::new("1")),
+            (Input::new("b"), State::new("1")),
+            (Input::new("a"), State::new("2")),
+        ]);
+        assert!(nfa.has_rule("X", "ɛ", "1"));
+        assert_eq!(nfa.transitions_from("Y"), vec![
+            (Input::range('0', '9'), State::new("Y")),
+            (Input::any(), State::new("Z")),
+        ]);
+        assert_eq!(nfa.transitions_from("Z"), vec![
+            (Input::new("."), State::new("Y")),
+            (Input::not(["x", "y"]), State::new("Z")),
+        ]);
+        assert!(nfa.accepts(["b", "a", "b"].into_iter()).unwrap());
+
+        for (src, line) in [
+            ("initial: X\nX -a->", 2),
+            ("initial: X\n\n# comment\nX a Y", 4),
+            ("initial:\nX -a-> Y", 1),
+            ("X --> Y", 1),
+        ] {
+            assert!(matches!(NFA::from_dsl(src), Err(Error::IllegalLine(l, _)) if l == line), "{}", src);
+        }
+    }
+
+    #[test]
+    fn to_dsl_test() {
+        let mut nfa = example_nfa();
+        assert_eq!(NFA::from_dsl(&nfa.to_dsl()).unwrap(), nfa);
+        assert!(nfa.to_dsl().starts_with("initial: X\nfinal: Y\n1 -a-> 3\n1 -b-> 4\n"));
+
+        // 与 DSL 语法冲突的输入字符被转义
+        for input in [Input::new("."), Input::new("[a-z]"), Input::new("\\"), Input::new("[^ab]"),
+                      Input::range('a', 'z'), Input::any(), Input::not(["a", ","]), Input::not(["if"]), Input::not(["if", "x"])] {
+            nfa.add_transfer_input("X", input, "Y").unwrap();
+        }
+        assert_eq!(NFA::from_dsl(&nfa.to_dsl()).unwrap(), nfa);
+
+        // 含有空白、为空串、以 `#` 开头或形如 `initial:` 的状态 id 与输入同样能原样解析回来
+        let mut nfa = NFA::new();
+        let ids = ["a b", "", "#x", "initial:", "final:y", "\\s", "x\\", "\t\u{3000}", "\\e\\u{41}"];
+        nfa.add_initial_states(ids.iter().copied()).unwrap();
+        nfa.add_finite_states(ids.iter().copied()).unwrap();
+        for (i, id) in ids.iter().enumerate() {
+            nfa.add_transfer_rule(id, ids[(i + 1) % ids.len()], ids[(i + 2) % ids.len()]).unwrap();
+        }
+        for input in [Input::range(' ', '~'), Input::not([" ", "\n"]), Input::not(["a b"]), Input::new("\\ ")] {
+            nfa.add_transfer_input("initial:", input, "#x").unwrap();
+        }
+        let dsl = nfa.to_dsl();
+        assert!(dsl.contains("\na\\sb -\\e-> \\#x\n"));
+        assert!(dsl.contains("\ninitial\\: -final\\:y-> \\\\s\n"));
+        assert_eq!(NFA::from_dsl(&dsl).unwrap(), nfa);
+        assert!(matches!(NFA::from_dsl("initial: X\nX -a-> \\u{zz}"), Err(Error::IllegalLine(2, _))));
+    }
+
+    #[test]
+    fn json_error_test() {
+        let expect_error = |json: &str, message: &str| match NFA::from_json(json) {
+            Err(Error::IllegalArgument(e)) => assert!(e.contains(message), "{}", e),
+            _ => panic!("expected an error for {}", json),
+        };
+        expect_error(r#"["X"]"#, "must be an object");
+        expect_error(r#"{"finite": [], "rules": []}"#, "`initial`");
+        expect_error(r#"{"initial": ["X"], "finite": [1], "rules": []}"#, "`finite`");
+        expect_error(r#"{"initial": ["X"], "finite": [], "rules": {}}"#, "`rules`");
+        expect_error(r#"{"initial": ["X"], "finite": [], "rules": [{"from": "X", "to": "Y"}]}"#, "`rules[].input`");
+        expect_error(r#"{"initial": ["X"], "finite": [], "rules": [] "#, "JSON");
+        expect_error(&"[".repeat(10000), "too deep");
+        expect_error(&format!("{}{}", "{\"a\": ".repeat(10000), "null"), "too deep");
+        let nested = format!("{}{}", "[".repeat(100), "]".repeat(100));
+        expect_error(&format!(r#"{{"initial": {}, "finite": [], "rules": []}}"#, nested), "`initial`");
+    }
+
+    #[test]
+    fn lazy_epsilon_closure_test() {
+        let nfa = example_nfa();
+        let x = State::new("X");
+        assert!(matches!(nfa.try_get_epsilon_closure(once(&x)), Err(Error::Uninitialized(_))));
+        assert!(nfa.accepts(["a", "a"].into_iter()).unwrap());
+        assert!(!nfa.accepts(["a", "b"].into_iter()).unwrap());
+        assert!(nfa.epsilon_closure_matrix.get().is_some());
+        assert_eq!(nfa.try_get_epsilon_closure(once(&x)).unwrap(),
+                   BTreeSet::from([State::new("X"), State::new("5"), State::new("1")]));
+        assert_eq!(nfa.get_epsilon_closure(once(&x)).unwrap(), nfa.try_get_epsilon_closure(once(&x)).unwrap());
+    }
+
+    #[test]
+    fn stale_epsilon_closure_test() {
+        let mut nfa = single("a");
+        nfa.calc_epsilon_closure_matrix();
+        assert!(!nfa.accepts([].into_iter()).unwrap());
+        nfa.add_transfer_rule("X", "ɛ", "Z").unwrap();
+        nfa.add_transfer_rule("Z", "ɛ", "Y").unwrap();
+        assert_eq!(nfa.get_epsilon_closure(once(&State::new("X"))).unwrap(),
+                   BTreeSet::from([State::new("X"), State::new("Y"), State::new("Z")]));
+        assert!(nfa.accepts([].into_iter()).unwrap());
+        nfa.add_finite_states(once("W")).unwrap();
+        nfa.add_transfer_rule("Y", "b", "W").unwrap();
+        assert!(nfa.accepts(["b"].into_iter()).unwrap());
+        nfa.calc_epsilon_closure_matrix();
+        assert!(nfa.accepts(["b"].into_iter()).unwrap());
+        assert!(nfa.accepts(["a", "b"].into_iter()).unwrap());
+    }
+
+    #[test]
+    fn foreign_state_epsilon_closure_test() {
+        let mut nfa = single("a");
+        assert!(matches!(nfa.get_epsilon_closure(once(&State::new("nowhere"))),
+                         Err(Error::StateNotFound(s)) if s == "nowhere"));
+        assert!(matches!(nfa.try_get_epsilon_closure(once(&State::new("nowhere"))),
+                         Err(Error::StateNotFound(s)) if s == "nowhere"));
+        // 没有转换规则的初态也能查询 ɛ 闭包
+        nfa.add_initial_states(once("lonely")).unwrap();
+        assert_eq!(nfa.get_epsilon_closure(once(&State::new("lonely"))).unwrap(),
+                   BTreeSet::from([State::new("lonely")]));
+        assert!(nfa.accepts(["a"].into_iter()).unwrap());
+    }
+
+    #[test]
+    fn epsilon_reachable_from_test() {
+        let nfa = example_nfa();
+        assert_eq!(nfa.epsilon_reachable_from(&State::new("X")).unwrap(),
+                   &BTreeSet::from(["X", "5", "1"].map(State::new)));
+        assert_eq!(nfa.epsilon_reachable_from(&State::new("Y")).unwrap(),
+                   &BTreeSet::from([State::new("Y")]));
+        assert!(matches!(nfa.epsilon_reachable_from(&State::new("nowhere")), Err(Error::StateNotFound(s)) if s == "nowhere"));
+    }
+
+    #[test]
+    fn epsilon_closure_detailed_test() {
+        let nfa = example_nfa();
+        let edges = nfa.epsilon_closure_detailed(&State::new("2")).unwrap();
+        assert_eq!(edges, vec![(State::new("2"), State::new("6")), (State::new("6"), State::new("Y"))]);
+        // 不需要 ɛ 闭包矩阵，且所有弧构成一棵以 start 为根的树
+        assert!(nfa.epsilon_closure_matrix.get().is_none());
+        let start = State::new("X");
+        let edges = nfa.epsilon_closure_detailed(&start).unwrap();
+        let mut reached = BTreeSet::from([start.clone()]);
+        for (from_state, to_state) in &edges {
+            assert!(reached.contains(from_state));
+            reached.insert(to_state.clone());
+        }
+        assert_eq!(reached, nfa.get_epsilon_closure(once(&start)).unwrap());
+        assert!(nfa.epsilon_closure_detailed(&State::new("Y")).unwrap().is_empty());
+        assert!(matches!(nfa.epsilon_closure_detailed(&State::new("nowhere")), Err(Error::StateNotFound(s)) if s == "nowhere"));
+    }
+
+    #[test]
+    fn epsilon_cycle_test() {
+        let mut nfa = example_nfa();
+        assert!(!nfa.has_epsilon_cycle());
+        assert!(nfa.epsilon_cycle_states().is_empty());
+        nfa.add_transfer_rule("1", "ɛ", "X").unwrap();
+        nfa.add_transfer_rule("Y", "ɛ", "Y").unwrap();
+        assert!(nfa.has_epsilon_cycle());
+        assert_eq!(nfa.epsilon_cycle_states(), BTreeSet::from(["X", "5", "1", "Y"].map(State::new)));
+        // ɛ 环不影响接受的语言
+        assert!(nfa.accepts(["a", "a"].into_iter()).unwrap());
+        assert!(!nfa.accepts(["a", "b"].into_iter()).unwrap());
+    }
+
+    #[test]
+    fn range_test() {
+        let mut nfa = NFA::new();
+        nfa.add_initial_states(once("X")).unwrap();
+        nfa.add_finite_states(once("Y")).unwrap();
+        nfa.add_transfer_input("X", Input::range('a', 'z'), "Y").unwrap();
+        nfa.add_transfer_rule("Y", "0", "Y").unwrap();
+        assert!(nfa.accepts(["m"].into_iter()).unwrap());
+        assert!(nfa.accepts(["a", "0", "0"].into_iter()).unwrap());
+        assert!(!nfa.accepts(["0"].into_iter()).unwrap());
+        let dfa = nfa.to_dfa().unwrap();
+        assert!(dfa.accepts(&["z", "0"]));
+        assert!(!dfa.accepts(&["0"]));
+        let json = nfa.to_json();
+        assert!(json.contains("\"input\": [\"a\", \"z\"]"));
+        assert!(NFA::from_json(&json).unwrap().accepts(["q"].into_iter()).unwrap());
+    }
+
+    #[test]
+    fn state_id_gen_test() {
+        // 两个片段构造器共用同一个生成器
+        let chain = |ids: &mut StateIdGen, symbols: &[&str]| {
+            let mut nfa = NFA::new();
+            let start = ids.fresh();
+            nfa.add_initial_states(once(start.as_str())).unwrap();
+            let accept = symbols.iter()
+                .fold(start, |from, symbol| nfa.add_fresh_transfer_input(ids, &from, Input::new(*symbol)));
+            nfa.add_finite_states(once(accept.as_str())).unwrap();
+            nfa
+        };
+        let loop_on = |ids: &mut StateIdGen, symbol: &str| {
+            let mut nfa = NFA::new();
+            let start = ids.fresh();
+            let accept = nfa.add_fresh_transfer_input(ids, &start, Input::new(symbol));
+            nfa.add_transfer_rule(&accept, "ɛ", &start).unwrap();
+            nfa.add_initial_states(once(start.as_str())).unwrap();
+            nfa.add_finite_states(once(accept.as_str())).unwrap();
+            nfa
+        };
+        let mut ids = StateIdGen::new();
+        let first = chain(&mut ids, &["a", "b"]);
+        let second = loop_on(&mut ids, "c");
+        let third = chain(&mut ids, &["d"]);
+        assert_eq!(first.get_all_states_iter().map(|s| s.state_id.as_str()).collect::<Vec<_>>(), vec!["q0", "q1", "q2"]);
+        let mut seen = BTreeSet::new();
+        for nfa in [&first, &second, &third] {
+            assert!(nfa.get_all_states_iter().all(|s| seen.insert(s.clone())));
+        }
+        assert_eq!(seen.len(), 7);
+        assert_eq!(ids.fresh(), "q7");
+        assert_eq!(StateIdGen::with_prefix("").fresh(), "0");
+
+        // 组合子共用同一个生成器时，各自得到的状态 id 互不相同，组合后也不会冲突
+        let mut ids = StateIdGen::new();
+        let ab = single("a").concat_with_ids(single("b"), &mut ids);
+        let c_star = single("c").star_with_ids(&mut ids);
+        let ab_ids = ab.get_all_states_iter().cloned().collect::<BTreeSet<_>>();
+        assert!(c_star.get_all_states_iter().all(|s| !ab_ids.contains(s)));
+        let nfa = ab.union_with_ids(c_star, &mut ids).plus_with_ids(&mut ids).optional_with_ids(&mut ids);
+        assert!(nfa.get_all_states_iter().all(|s| s.state_id.starts_with('q') && !s.state_id.contains('.')));
+        for (word, expected) in [(vec![], true), (vec!["a", "b", "c", "c"], true), (vec!["a", "c"], false)] {
+            assert_eq!(nfa.accepts(word.into_iter()).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn builder_test() {
+        let nfa = NfaBuilder::new()
+            .initial("X")
+            .finite("Y")
+            .rule("X", "a", "Y")
+            .rule("X", "ɛ", "Y")
+            .build()
+            .unwrap();
+        assert!(nfa.accepts(["a"].into_iter()).unwrap());
+        assert!(nfa.accepts([].into_iter()).unwrap());
+        assert!(!nfa.accepts(["b"].into_iter()).unwrap());
+        assert!(matches!(NfaBuilder::new().finite("Y").rule("X", "a", "Y").build(),
+                         Err(Error::IllegalArgument(_))));
+    }
+
+    #[test]
+    fn trace_test() {
+        let mut nfa = example_nfa();
+        assert!(matches!(nfa.trace(&["a"]), Err(Error::Uninitialized(_))));
+        nfa.calc_epsilon_closure_matrix();
+        let steps = nfa.trace(&["a", "a", "b"]).unwrap();
+        assert_eq!(steps.len(), 4);
+        assert_eq!(steps[0], BTreeSet::from(["X", "5", "1"].map(State::new)));
+        assert_eq!(steps.iter().map(BTreeSet::len).collect::<Vec<_>>(), vec![3, 3, 6, 5]);
+        assert!(steps[3].contains(&State::new("Y")));
+        assert_eq!(nfa.trace(&[]).unwrap(), vec![steps[0].clone()]);
+        // 卡住之后活动状态集为空
+        let mut nfa = single("a");
+        nfa.calc_epsilon_closure_matrix();
+        assert!(nfa.trace(&["b", "a"]).unwrap()[1..].iter().all(BTreeSet::is_empty));
+    }
+
+    #[test]
+    fn statistics_test() {
+        let mut nfa = example_nfa();
+        assert_eq!(nfa.num_edges(), 12);
+        assert_eq!(nfa.num_epsilon_edges(), 4);
+        assert_eq!(nfa.alphabet_size(), 2);
+        nfa.calc_epsilon_closure_matrix();
+        let dfa = nfa.to_dfa().unwrap();
+        assert_eq!(dfa.num_epsilon_edges(), 0);
+        assert!(dfa.num_edges() <= dfa.get_states_num() * dfa.alphabet_size());
+        assert!(dfa.get_states_num() <= 1 << nfa.get_states_num());
+    }
+
+    #[test]
+    fn normalize_ids_test() {
+        let mut nfa = single("a").union(single("b"));
+        nfa.normalize_ids();
+        let ids = nfa.get_all_states_iter().map(|s| s.state_id.clone()).collect::<BTreeSet<_>>();
+        assert_eq!(ids, (0..nfa.get_states_num()).map(|i| i.to_string()).collect());
+        assert_eq!(nfa.initial_states, BTreeSet::from([State::new("0")]));
+        assert!(nfa.accepts(["a"].into_iter()).unwrap());
+        assert!(nfa.accepts(["b"].into_iter()).unwrap());
+        assert!(!nfa.accepts(["a", "b"].into_iter()).unwrap());
+    }
+
+    #[test]
+    fn to_table_test() {
+        let table = example_nfa().to_table();
+        let lines = table.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0].split('|').map(str::trim).collect::<Vec<_>>(), vec!["", "a", "b", "ε"]);
+        assert!(lines.iter().any(|l| l.starts_with(" -> X ")));
+        assert!(lines.iter().any(|l| l.starts_with("  * Y ")));
+        assert!(lines.iter().any(|l| l.starts_with("    5 ") && l.contains("| 1")));
+        assert_eq!(lines.len(), 9);
+    }
+
+    #[test]
+    fn eq_test() {
+        let mut nfa = example_nfa();
+        assert_eq!(nfa, example_nfa());
+        // ɛ 闭包矩阵是否已计算不影响相等性
+        nfa.calc_epsilon_closure_matrix();
+        assert_eq!(nfa, example_nfa());
+        nfa.add_transfer_rule("Y", "a", "X").unwrap();
+        assert_ne!(nfa, example_nfa());
+    }
+
+    #[test]
+    fn clone_test() {
+        let mut nfa = single("a");
+        nfa.calc_epsilon_closure_matrix();
+        let snapshot = nfa.clone();
+        assert!(snapshot.try_get_epsilon_closure(once(&State::new("X"))).is_ok());
+        nfa.add_transfer_rule("X", "ɛ", "Y").unwrap();
+        assert!(nfa.accepts([].into_iter()).unwrap());
+        assert!(!snapshot.accepts([].into_iter()).unwrap());
+        assert_eq!(snapshot, single("a"));
+    }
+
+    #[test]
+    fn from_table_test() {
+        let nfa = NFA::from_table("X", &["Y"], &[
+            ("X", "ɛ", "5"), ("5", "a", "5"), ("5", "b", "5"), ("5", "ɛ", "1"),
+            ("1", "a", "3"), ("3", "a", "2"), ("1", "b", "4"), ("4", "b", "2"),
+            ("2", "ɛ", "6"), ("6", "a", "6"), ("6", "b", "6"), ("6", "ɛ", "Y"),
+        ]).unwrap();
+        assert_eq!(nfa, example_nfa());
+    }
+
+    #[test]
+    fn remove_transfer_rule_test() {
+        let mut nfa = example_nfa();
+        assert!(nfa.accepts(["a", "a"].into_iter()).unwrap());
+        nfa.remove_transfer_rule("3", "a", "2").unwrap();
+        assert!(!nfa.adjacency_matrix[&State::new("3")].contains_key(&State::new("2")));
+        assert!(!nfa.accepts(["a", "a"].into_iter()).unwrap());
+        assert!(nfa.accepts(["b", "b"].into_iter()).unwrap());
+        assert!(nfa.feasible_inputs.contains(&Input::new("a")));
+        assert!(matches!(nfa.remove_transfer_rule("3", "a", "2"), Err(Error::IllegalArgument(_))));
+        assert!(matches!(nfa.remove_transfer_rule("nowhere", "a", "2"), Err(Error::StateNotFound(s)) if s == "nowhere"));
+        assert!(matches!(nfa.remove_transfer_rule("3", "a", "nowhere"), Err(Error::StateNotFound(s)) if s == "nowhere"));
+
+        let mut nfa = single("a");
+        nfa.add_transfer_rule("X", "b", "Y").unwrap();
+        nfa.remove_transfer_rule("X", "a", "Y").unwrap();
+        assert_eq!(nfa.feasible_inputs, BTreeSet::from([Input::new("b")]));
+        assert!(nfa.adjacency_matrix[&State::new("X")].contains_key(&State::new("Y")));
+    }
+
+    #[test]
+    fn display_test() {
+        let nfa = example_nfa();
+        let display = format!("{}", nfa);
+        assert!(display.contains("initial_states: {X}"));
+        assert!(display.contains("X => {ɛ} => 5"));
+        assert_eq!(display, format!("{:?}", nfa));
+    }
+
+    #[test]
+    fn to_dot_test() {
+        let nfa = example_nfa();
+        let dot = nfa.to_dot();
+        assert!(dot.starts_with("digraph {"));
+        assert!(dot.contains("\"Y\" [shape=doublecircle];"));
+        assert!(dot.contains("\"X\" [shape=circle];"));
+        assert!(dot.contains("__start0 -> \"X\";"));
+        assert!(dot.contains("\"X\" -> \"5\" [label=\"ε\"];"));
+        assert!(dot.contains("\"5\" -> \"5\" [label=\"a,b\"];"));
+        assert!(dot.contains("\"1\" -> \"3\" [label=\"a\"];"));
     }
 }
\ No newline at end of file
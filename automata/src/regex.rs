@@ -0,0 +1,178 @@
+use std::iter::{once, Peekable};
+use std::str::Chars;
+use crate::automaton::FiniteAutomaton;
+use crate::input::Input;
+use crate::nfa::{NFA, StateIdGen};
+use crate::result::{Error, IResult};
+
+/// 从正则表达式构建 NFA，使用 Thompson 构造法
+/// 支持连接、并 (`|`)、闭包 (`*`) 与括号，每个字符为一个输入字符，可以使用 `\` 转义元字符
+/// `ɛ` 表示空串而不是输入字符，因此不能出现在 pattern 中 (空串可写作 `()`)；括号至多嵌套 128 层
+/// 构造出的 NFA 以从 0 开始的数字作为状态 id，有唯一的初态和唯一的终态
+pub fn from_regex(pattern: &str) -> IResult<NFA> {
+    let mut parser = Parser {
+        chars: pattern.chars().peekable(),
+        nfa: NFA::new(),
+        ids: StateIdGen::with_prefix(""),
+        depth: 0,
+    };
+    let (start_state_id, accept_state_id) = parser.parse_union()?;
+    if parser.chars.next().is_some() { // 只有多余的右括号会使解析提前结束
+        return Err(Error::IllegalArgument("Unbalanced parentheses: unexpected ')'."));
+    }
+    let mut nfa = parser.nfa;
+    nfa.add_initial_states(once(start_state_id.as_str()))?;
+    nfa.add_finite_states(once(accept_state_id.as_str()))?;
+    Ok(nfa)
+}
+
+/// 括号的最大嵌套层数，避免解析过深的 pattern 时栈溢出
+const MAX_DEPTH: usize = 128;
+
+/// NFA 片段，由 (初态 id, 终态 id) 表示
+type Fragment = (String, String);
+
+/// 递归下降解析器，边解析边构造 NFA
+/// union  := concat ('|' concat)*
+/// concat := star*
+/// star   := atom '*'*
+/// atom   := char | '\' char | '(' union ')'
+struct Parser<'p> {
+    chars: Peekable<Chars<'p>>,
+    nfa: NFA,
+    /// 用于生成新的状态 id
+    ids: StateIdGen,
+    /// 当前所在的括号嵌套层数
+    depth: usize,
+}
+
+impl<'p> Parser<'p> {
+    /// 构造一个接受单个输入字符 input_str 的片段
+    fn symbol(&mut self, input_str: &str) -> IResult<Fragment> {
+        let start = self.ids.fresh();
+        let accept = self.nfa.add_fresh_transfer_input(&mut self.ids, &start, Input::new(input_str));
+        Ok((start, accept))
+    }
+
+    fn parse_union(&mut self) -> IResult<Fragment> {
+        let first = self.parse_concat()?;
+        if self.chars.peek() != Some(&'|') {
+            return Ok(first);
+        }
+        let start = self.ids.fresh();
+        let accept = self.ids.fresh();
+        let mut alternatives = vec![first];
+        while self.chars.next_if_eq(&'|').is_some() {
+            alternatives.push(self.parse_concat()?);
+        }
+        for (alternative_start, alternative_accept) in alternatives {
+            self.nfa.add_transfer_rule(&start, "ɛ", &alternative_start)?;
+            self.nfa.add_transfer_rule(&alternative_accept, "ɛ", &accept)?;
+        }
+        Ok((start, accept))
+    }
+
+    fn parse_concat(&mut self) -> IResult<Fragment> {
+        let mut fragment: Option<Fragment> = None;
+        while let Some(c) = self.chars.peek() {
+            if *c == '|' || *c == ')' {
+                break;
+            }
+            let next = self.parse_star()?;
+            fragment = Some(match fragment {
+                Some((start, accept)) => {
+                    self.nfa.add_transfer_rule(&accept, "ɛ", &next.0)?;
+                    (start, next.1)
+                },
+                None => next,
+            });
+        }
+        match fragment {
+            Some(fragment) => Ok(fragment),
+            None => self.symbol("ɛ"), // 空串
+        }
+    }
+
+    fn parse_star(&mut self) -> IResult<Fragment> {
+        let mut fragment = self.parse_atom()?;
+        while self.chars.next_if_eq(&'*').is_some() {
+            let (inner_start, inner_accept) = fragment;
+            let start = self.ids.fresh();
+            let accept = self.ids.fresh();
+            self.nfa.add_transfer_rule(&start, "ɛ", &inner_start)?;
+            self.nfa.add_transfer_rule(&start, "ɛ", &accept)?;
+            self.nfa.add_transfer_rule(&inner_accept, "ɛ", &inner_start)?;
+            self.nfa.add_transfer_rule(&inner_accept, "ɛ", &accept)?;
+            fragment = (start, accept);
+        }
+        Ok(fragment)
+    }
+
+    fn parse_atom(&mut self) -> IResult<Fragment> {
+        match self.chars.next() {
+            Some('(') => {
+                if self.depth == MAX_DEPTH {
+                    return Err(Error::IllegalArgument("Parentheses are nested too deeply."));
+                }
+                self.depth += 1;
+                let fragment = self.parse_union()?;
+                self.depth -= 1;
+                if self.chars.next_if_eq(&')').is_none() {
+                    return Err(Error::IllegalArgument("Unbalanced parentheses: missing ')'."));
+                }
+                Ok(fragment)
+            },
+            Some('*') => Err(Error::IllegalArgument("Nothing to repeat before '*'.")),
+            Some('ɛ') => Err(Error::IllegalArgument("'ɛ' is not an input symbol, use '()' for the empty string.")),
+            Some('\\') => match self.chars.next() {
+                Some('ɛ') => Err(Error::IllegalArgument("'ɛ' is not an input symbol and cannot be escaped.")),
+                Some(c) => self.symbol(c.encode_utf8(&mut [0; 4])),
+                None => Err(Error::IllegalArgument("Trailing '\\' at the end of pattern.")),
+            },
+            Some(c) => self.symbol(c.encode_utf8(&mut [0; 4])),
+            None => unreachable!("parse_concat never calls parse_atom at the end of pattern"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accepts(nfa: &NFA, input: &str) -> bool {
+        let chars = input.chars().map(String::from).collect::<Vec<_>>();
+        nfa.accepts(chars.iter().map(String::as_str)).unwrap()
+    }
+
+    #[test]
+    fn from_regex_test() {
+        let mut nfa = from_regex("a(b|c)*").unwrap();
+        nfa.calc_epsilon_closure_matrix();
+        assert!(accepts(&nfa, "a"));
+        assert!(accepts(&nfa, "ab"));
+        assert!(accepts(&nfa, "acbbc"));
+        assert!(!accepts(&nfa, ""));
+        assert!(!accepts(&nfa, "b"));
+        assert!(!accepts(&nfa, "aa"));
+        assert!(!accepts(&nfa, "abd"));
+    }
+
+    #[test]
+    fn escape_and_empty_test() {
+        let mut nfa = from_regex("\\(|x()").unwrap();
+        nfa.calc_epsilon_closure_matrix();
+        assert!(accepts(&nfa, "("));
+        assert!(accepts(&nfa, "x"));
+        assert!(!accepts(&nfa, "x("));
+    }
+
+    #[test]
+    fn malformed_test() {
+        for pattern in ["(a", "a)", "(a|b))", "*a", "a|*", "a\\", "ɛ", "a\\ɛ", "(ɛ)*"] {
+            assert!(matches!(from_regex(pattern), Err(Error::IllegalArgument(_))), "{}", pattern);
+        }
+        assert!(matches!(from_regex(&"(".repeat(2000)), Err(Error::IllegalArgument(e)) if e.contains("too deeply")));
+        let nested = format!("{}a{}", "(".repeat(128), ")".repeat(128));
+        assert!(from_regex(&nested).is_ok());
+    }
+}
@@ -7,6 +7,9 @@ pub enum Error {
     IllegalArgument(&'static str),
     UnsupportedOperation(&'static str),
     Uninitialized(&'static str),
+    StateNotFound(String),
+    /// 文本输入的第 line 行格式错误，行号从 1 开始
+    IllegalLine(usize, &'static str),
 }
 
 impl Display for Error {
@@ -20,7 +23,25 @@ impl Display for Error {
             },
             Error::Uninitialized(e) => {
                 write!(f, "Uninitialized: {}", e)
+            },
+            Error::StateNotFound(state_id) => {
+                write!(f, "State Not Found: {}", state_id)
+            },
+            Error::IllegalLine(line, e) => {
+                write!(f, "Illegal Argument at line {}: {}", line, e)
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_test() {
+        assert_eq!(Error::StateNotFound("q0".to_string()).to_string(), "State Not Found: q0");
+        assert_eq!(Error::IllegalArgument("bad").to_string(), "Illegal Argument: bad");
+        assert_eq!(Error::IllegalLine(3, "bad").to_string(), "Illegal Argument at line 3: bad");
+    }
+}
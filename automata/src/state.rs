@@ -1,6 +1,9 @@
 use std::fmt::{Debug, Formatter};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 #[derive(PartialOrd, Ord, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
 pub struct State {
     pub state_id: String,
 }
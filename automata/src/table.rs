@@ -0,0 +1,54 @@
+use std::collections::{BTreeMap, BTreeSet};
+use crate::edge::Edge;
+use crate::input::Input;
+use crate::state::State;
+
+/// 将一个有限自动机渲染为状态转换表
+/// 每行为一个状态，初态以 `->` 标记，终态以 `*` 标记；每列为一个输入，单元格中列出所有目标状态
+pub(crate) fn to_table(initial_states: &BTreeSet<State>,
+                       finite_states: &BTreeSet<State>,
+                       inputs: &[Input],
+                       adjacency_matrix: &BTreeMap<State, BTreeMap<State, Edge>>) -> String {
+    let mut rows = Vec::new();
+    rows.push(once_then(String::new(), inputs.iter()
+        .map(|input| if input.is_epsilon() { "ε".to_string() } else { input.to_string() })));
+    let states = adjacency_matrix.keys()
+        .chain(initial_states.iter())
+        .chain(finite_states.iter())
+        .collect::<BTreeSet<_>>();
+    for state in states {
+        let marker = format!("{}{}",
+            if initial_states.contains(state) { "->" } else { "" },
+            if finite_states.contains(state) { "*" } else { "" });
+        let cells = inputs.iter().map(|input| adjacency_matrix.get(state).into_iter()
+            .flat_map(|to_map| to_map.iter())
+            .filter(|(_, edge)| edge.input_set.contains(input))
+            .map(|(to_state, _)| to_state.state_id.as_str())
+            .collect::<Vec<_>>()
+            .join(","));
+        rows.push(once_then(format!("{:>3} {}", marker, state.state_id), cells));
+    }
+
+    // 每列的宽度为该列中最长单元格的宽度
+    let mut widths = vec![0; inputs.len() + 1];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    rows.iter()
+        .map(|row| row.iter().zip(&widths)
+            .map(|(cell, width)| format!("{}{}", cell, " ".repeat(width - cell.chars().count())))
+            .collect::<Vec<_>>()
+            .join(" | ")
+            .trim_end()
+            .to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 返回以 first 开头，之后为 rest 中各元素的 `Vec`
+fn once_then<I>(first: String, rest: I) -> Vec<String>
+    where I: Iterator<Item = String> {
+    std::iter::once(first).chain(rest).collect()
+}
@@ -0,0 +1,37 @@
+use automata::{dfa, nfa};
+use automata::result::Error;
+
+#[test]
+fn nfa_macro_test() {
+    let nfa = nfa!(
+        initial_states: "X";
+        finite_states: "Y";
+        transfer_rules: "X" => "a" => "Y",
+                        "Y" => "b" => "Y",
+                        "X" => "ɛ" => "Y"
+    ).unwrap();
+    assert!(nfa.accepts(["a", "b"].into_iter()).unwrap());
+    assert!(nfa.accepts(["b", "b"].into_iter()).unwrap());
+    assert!(!nfa.accepts(["b", "a"].into_iter()).unwrap());
+}
+
+#[test]
+fn dfa_macro_test() {
+    let dfa = dfa!(
+        initial_state: "0";
+        finite_states: "1", "2";
+        transfer_rules: "0" => "a" => "1",
+                        "1" => "b" => "2"
+    ).unwrap();
+    assert!(dfa.accepts(&["a"]));
+    assert!(dfa.accepts(&["a", "b"]));
+    assert!(!dfa.accepts(&["b"]));
+
+    let nondeterministic = dfa!(
+        initial_state: "0";
+        finite_states: "1";
+        transfer_rules: "0" => "a" => "1",
+                        "0" => "a" => "0"
+    );
+    assert!(matches!(nondeterministic, Err(Error::UnsupportedOperation(_))));
+}